@@ -19,6 +19,7 @@ fn generate_single_primitive_geometry() {
             Vec3::new(10.0, 0.2, 10.0).into(),
         ))),
         area: None,
+        flags: 0,
     }];
     let nav_mesh_settings = NavMeshSettings {
         cell_width: 0.25,
@@ -57,6 +58,7 @@ fn generate_many_primitive_geometry() {
                 Vec3::new(10.0, 0.2, 10.0).into(),
             ))),
             area: None,
+            flags: 0,
         },
         GeometryCollection {
             transform: Transform::from_xyz(5.0, 1.0, 0.0),
@@ -64,6 +66,7 @@ fn generate_many_primitive_geometry() {
                 Vec3::new(1.0, 1.0, 1.0).into(),
             ))),
             area: None,
+            flags: 0,
         },
         GeometryCollection {
             transform: Transform::from_xyz(-5.0, 1.0, 2.0),
@@ -71,6 +74,7 @@ fn generate_many_primitive_geometry() {
                 Vec3::new(4.0, 1.0, 1.0).into(),
             ))),
             area: None,
+            flags: 0,
         },
         GeometryCollection {
             transform: Transform::from_xyz(-2.5, 2.0, 2.0),
@@ -78,6 +82,7 @@ fn generate_many_primitive_geometry() {
                 Vec3::new(1.0, 2.0, 1.0).into(),
             ))),
             area: None,
+            flags: 0,
         },
         GeometryCollection {
             transform: Transform::from_xyz(-2.5, 2.0, -2.0),
@@ -85,6 +90,7 @@ fn generate_many_primitive_geometry() {
                 Vec3::new(1.0, 2.0, 1.0).into(),
             ))),
             area: None,
+            flags: 0,
         },
     ];
     let nav_mesh_settings = NavMeshSettings {
@@ -1,16 +1,16 @@
 use std::num::NonZeroU16;
 
 use bevy::{
-    platform::collections::HashMap,
+    math::Vec3Swizzles,
     prelude::{Transform, UVec2, Vec3},
 };
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use oxidized_navigation::{
     build_tile_sync,
     conversion::{ColliderType, GeometryCollection, GeometryToConvert},
-    query::find_path,
+    query::{find_path, NavMeshQuery},
     tiles::{NavMeshTile, NavMeshTiles},
-    NavMeshSettings,
+    Area, NavMeshSettings,
 };
 use parry3d::shape::Cuboid;
 
@@ -24,6 +24,7 @@ fn generate_single_primitive_geometry(nav_mesh_settings: &NavMeshSettings) -> Na
             Vec3::new(10.0, 0.2, 10.0).into(),
         ))),
         area: None,
+        flags: 0,
     }];
 
     build_tile_sync(
@@ -45,6 +46,7 @@ fn generate_many_primitive_geometry(nav_mesh_settings: &NavMeshSettings) -> NavM
                 Vec3::new(10.0, 0.2, 10.0).into(),
             ))),
             area: None,
+            flags: 0,
         },
         GeometryCollection {
             transform: Transform::from_xyz(5.0, 1.0, 0.0),
@@ -52,6 +54,7 @@ fn generate_many_primitive_geometry(nav_mesh_settings: &NavMeshSettings) -> NavM
                 Vec3::new(1.0, 1.0, 1.0).into(),
             ))),
             area: None,
+            flags: 0,
         },
         GeometryCollection {
             transform: Transform::from_xyz(-5.0, 1.0, 2.0),
@@ -59,6 +62,7 @@ fn generate_many_primitive_geometry(nav_mesh_settings: &NavMeshSettings) -> NavM
                 Vec3::new(4.0, 1.0, 1.0).into(),
             ))),
             area: None,
+            flags: 0,
         },
         GeometryCollection {
             transform: Transform::from_xyz(-2.5, 2.0, 2.0),
@@ -66,6 +70,7 @@ fn generate_many_primitive_geometry(nav_mesh_settings: &NavMeshSettings) -> NavM
                 Vec3::new(1.0, 2.0, 1.0).into(),
             ))),
             area: None,
+            flags: 0,
         },
         GeometryCollection {
             transform: Transform::from_xyz(-2.5, 2.0, -2.0),
@@ -73,6 +78,7 @@ fn generate_many_primitive_geometry(nav_mesh_settings: &NavMeshSettings) -> NavM
                 Vec3::new(1.0, 2.0, 1.0).into(),
             ))),
             area: None,
+            flags: 0,
         },
     ];
 
@@ -84,6 +90,64 @@ fn generate_many_primitive_geometry(nav_mesh_settings: &NavMeshSettings) -> NavM
     )
 }
 
+/// Ground plane covering the entire world with a grid of pillars to break it up into many polygons.
+fn generate_open_world_geometry(nav_mesh_settings: &NavMeshSettings) -> Vec<GeometryCollection> {
+    let half_extents = nav_mesh_settings.world_half_extents;
+
+    let mut geometry_collections = vec![GeometryCollection {
+        transform: Transform::from_xyz(0.0, 0.0, 0.0),
+        geometry_to_convert: GeometryToConvert::Collider(ColliderType::Cuboid(Cuboid::new(
+            Vec3::new(half_extents, 0.2, half_extents).into(),
+        ))),
+        area: Some(Area(0)),
+        flags: 0,
+    }];
+
+    let mut x = -half_extents + 5.0;
+    while x < half_extents {
+        let mut z = -half_extents + 5.0;
+        while z < half_extents {
+            geometry_collections.push(GeometryCollection {
+                transform: Transform::from_xyz(x, 2.0, z),
+                geometry_to_convert: GeometryToConvert::Collider(ColliderType::Cuboid(
+                    Cuboid::new(Vec3::new(1.0, 2.0, 1.0).into()),
+                )),
+                area: None,
+                flags: 0,
+            });
+
+            z += 8.0;
+        }
+        x += 8.0;
+    }
+
+    geometry_collections
+}
+
+fn generate_multi_tile_world(nav_mesh_settings: &NavMeshSettings) -> NavMeshTiles {
+    let mut nav_mesh = NavMeshTiles::default();
+
+    let max_tile = nav_mesh_settings.get_tile_containing_position(
+        Vec3::splat(nav_mesh_settings.world_half_extents - 0.01).xz(),
+    );
+    for x in 0..=max_tile.x {
+        for y in 0..=max_tile.y {
+            let tile_coord = UVec2::new(x, y);
+
+            let tile = build_tile_sync(
+                generate_open_world_geometry(nav_mesh_settings),
+                tile_coord,
+                Box::default(),
+                nav_mesh_settings,
+            );
+
+            nav_mesh.add_tile(tile_coord, tile, nav_mesh_settings);
+        }
+    }
+
+    nav_mesh
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     let nav_mesh_settings = NavMeshSettings {
         cell_width: 0.25,
@@ -103,24 +167,18 @@ fn criterion_benchmark(c: &mut Criterion) {
         experimental_detail_mesh_generation: None,
     };
 
-    let simple_tiles = NavMeshTiles {
-        tiles: vec![(
-            UVec2::ZERO,
-            generate_single_primitive_geometry(&nav_mesh_settings),
-        )]
-        .into_iter()
-        .collect(),
-        tile_generations: HashMap::default(),
-    };
-    let many_tiles = NavMeshTiles {
-        tiles: vec![(
-            UVec2::ZERO,
-            generate_many_primitive_geometry(&nav_mesh_settings),
-        )]
-        .into_iter()
-        .collect(),
-        tile_generations: HashMap::default(),
-    };
+    let mut simple_tiles = NavMeshTiles::default();
+    simple_tiles.add_tile(
+        UVec2::ZERO,
+        generate_single_primitive_geometry(&nav_mesh_settings),
+        &nav_mesh_settings,
+    );
+    let mut many_tiles = NavMeshTiles::default();
+    many_tiles.add_tile(
+        UVec2::ZERO,
+        generate_many_primitive_geometry(&nav_mesh_settings),
+        &nav_mesh_settings,
+    );
 
     c.bench_function("Simple Navigation", |b| {
         b.iter(|| {
@@ -148,5 +206,66 @@ fn criterion_benchmark(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, criterion_benchmark);
+fn multi_tile_benchmark(c: &mut Criterion) {
+    let nav_mesh_settings = NavMeshSettings {
+        cell_width: 0.25,
+        cell_height: 0.1,
+        tile_width: NonZeroU16::new(100).unwrap(),
+        world_half_extents: 100.0,
+        world_bottom_bound: -100.0,
+        max_traversable_slope_radians: (40.0_f32 - 0.1).to_radians(),
+        walkable_height: 20,
+        walkable_radius: 1,
+        step_height: 3,
+        min_region_area: 100,
+        max_region_area_to_merge_into: 500,
+        max_contour_simplification_error: 1.1,
+        max_edge_length: 80,
+        max_tile_generation_tasks: NonZeroU16::new(1),
+        experimental_detail_mesh_generation: None,
+    };
+
+    let multi_tiles = generate_multi_tile_world(&nav_mesh_settings);
+
+    c.bench_function("Multi-tile Navigation (Short)", |b| {
+        b.iter(|| {
+            black_box(find_path(
+                &multi_tiles,
+                &nav_mesh_settings,
+                Vec3::new(-10.0, 0.2, -10.0),
+                Vec3::new(10.0, 0.2, 10.0),
+                None,
+                None,
+            ))
+        })
+    });
+    c.bench_function("Multi-tile Navigation (Long)", |b| {
+        b.iter(|| {
+            black_box(find_path(
+                &multi_tiles,
+                &nav_mesh_settings,
+                Vec3::new(-95.0, 0.2, -95.0),
+                Vec3::new(95.0, 0.2, 95.0),
+                None,
+                None,
+            ))
+        })
+    });
+
+    let mut query = NavMeshQuery::default();
+    c.bench_function("Multi-tile Navigation (Long, Reused Query)", |b| {
+        b.iter(|| {
+            black_box(query.find_path(
+                &multi_tiles,
+                &nav_mesh_settings,
+                Vec3::new(-95.0, 0.2, -95.0),
+                Vec3::new(95.0, 0.2, 95.0),
+                None,
+                None,
+            ))
+        })
+    });
+}
+
+criterion_group!(benches, criterion_benchmark, multi_tile_benchmark);
 criterion_main!(benches);
@@ -0,0 +1,201 @@
+//! Module for requesting paths through components.
+//!
+//! Insert a [PathRequest] on an entity & the plugin finds a path from the entity's position to the target in the background, using the settings in the entity's [NavAgent].
+//! Once found the path is inserted as a [NavPath] component & the [PathRequest] is removed. If no path could be found a [PathRequestFailed] event is sent instead.
+//!
+//! Requests are run through the [PathfindingQueue], so its limit on concurrent tasks applies.
+
+use std::sync::Arc;
+
+use bevy::{
+    ecs::entity::EntityHashMap, platform::collections::HashMap, prelude::*,
+    transform::helper::TransformHelper,
+};
+
+use crate::{
+    query::{DefaultQueryFilter, FindPathError, PathStatus, QueryFilter},
+    tickets::{PathTicket, PathfindingFinished, PathfindingQueue, PathfindingRequest},
+    tiles::{NavMeshTiles, PolyRef},
+    Area,
+};
+
+/// Pathfinding settings for an entity. Used when resolving its [PathRequest]s.
+#[derive(Component, Clone, Default)]
+pub struct NavAgent {
+    /// Half extents of the box to search for a start & end polygon in. See [crate::query::find_polygon_path].
+    pub position_search_extents: Option<Vec3>,
+    /// Filter deciding which polygons may be traversed & at what cost, including per-area costs.
+    pub filter: DefaultQueryFilter,
+    /// Filter used instead of [NavAgent::filter] if set.
+    pub custom_filter: Option<Arc<dyn QueryFilter + Send + Sync>>,
+    /// Priority of the agent's requests in the [PathfindingQueue].
+    pub priority: i32,
+}
+
+impl NavAgent {
+    /// Setter for [NavAgent::position_search_extents]
+    pub fn with_position_search_extents(mut self, position_search_extents: Vec3) -> Self {
+        self.position_search_extents = Some(position_search_extents);
+        self
+    }
+
+    /// Setter for [NavAgent::filter]
+    pub fn with_filter(mut self, filter: DefaultQueryFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Sets the cost multiplier of ``area`` in [NavAgent::filter]. See [DefaultQueryFilter::with_area_cost].
+    pub fn with_area_cost(mut self, area: Area, cost: f32) -> Self {
+        self.filter = self.filter.with_area_cost(area, cost);
+        self
+    }
+
+    /// Setter for [NavAgent::custom_filter]
+    pub fn with_custom_filter(mut self, filter: impl QueryFilter + Send + Sync + 'static) -> Self {
+        self.custom_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Setter for [NavAgent::priority]
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Requests a path from the entity's position to ``target``. Replaced by a [NavPath] once found.
+///
+/// Changing the target while a request is running restarts it.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+#[require(NavAgent)]
+pub struct PathRequest {
+    pub target: Vec3,
+}
+
+/// A path found for a [PathRequest].
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct NavPath {
+    /// The string pulled path, from the entity's position when the path was requested to the target.
+    pub corners: Vec<Vec3>,
+    /// Whether the path reaches the target.
+    pub status: PathStatus,
+    /// The polygons the path crosses, in order from start to target.
+    pub polygons: Vec<PolyRef>,
+}
+
+impl NavPath {
+    /// Returns true if a tile the path crosses has been rebuilt or removed since the path was found. Changes to other tiles don't affect the path.
+    pub fn is_outdated(&self, nav_mesh: &NavMeshTiles) -> bool {
+        self.polygons
+            .iter()
+            .any(|polygon| !nav_mesh.is_valid_poly_ref(*polygon))
+    }
+}
+
+/// Event sent when no path could be found for an entity's [PathRequest]. The request & any previous [NavPath] are removed.
+#[derive(Event, Debug, Clone)]
+pub struct PathRequestFailed {
+    pub entity: Entity,
+    pub error: FindPathError,
+}
+
+/// Tickets of the [PathRequest]s currently in the [PathfindingQueue].
+#[derive(Resource, Default)]
+pub(crate) struct AgentPathTickets {
+    by_entity: EntityHashMap<PathTicket>,
+    by_ticket: HashMap<PathTicket, Entity>,
+}
+
+impl AgentPathTickets {
+    fn cancel(&mut self, entity: Entity, pathfinding_queue: &mut PathfindingQueue) {
+        if let Some(ticket) = self.by_entity.remove(&entity) {
+            self.by_ticket.remove(&ticket);
+            pathfinding_queue.cancel(ticket);
+        }
+    }
+}
+
+pub(crate) fn cancel_removed_path_requests_system(
+    mut removed_requests: RemovedComponents<PathRequest>,
+    mut pathfinding_queue: ResMut<PathfindingQueue>,
+    mut agent_path_tickets: ResMut<AgentPathTickets>,
+) {
+    for entity in removed_requests.read() {
+        agent_path_tickets.cancel(entity, &mut pathfinding_queue);
+    }
+}
+
+pub(crate) fn send_path_requests_system(
+    mut pathfinding_queue: ResMut<PathfindingQueue>,
+    mut agent_path_tickets: ResMut<AgentPathTickets>,
+    request_query: Query<(Entity, &PathRequest, &NavAgent, &GlobalTransform), Changed<PathRequest>>,
+    transform_helper: TransformHelper,
+) {
+    for (entity, path_request, nav_agent, global_transform) in request_query.iter() {
+        // Transforms aren't propagated until ``PostUpdate``, so the global transform of a freshly spawned entity is still the identity here.
+        let start_pos = transform_helper
+            .compute_global_transform(entity)
+            .unwrap_or(*global_transform)
+            .translation();
+
+        agent_path_tickets.cancel(entity, &mut pathfinding_queue);
+
+        let filter = nav_agent
+            .custom_filter
+            .clone()
+            .unwrap_or_else(|| Arc::new(nav_agent.filter.clone()));
+
+        let ticket = pathfinding_queue.submit(PathfindingRequest {
+            start_pos,
+            end_pos: path_request.target,
+            position_search_extents: nav_agent.position_search_extents,
+            filter: Some(filter),
+            priority: nav_agent.priority,
+        });
+
+        agent_path_tickets.by_entity.insert(entity, ticket);
+        agent_path_tickets.by_ticket.insert(ticket, entity);
+    }
+}
+
+pub(crate) fn receive_paths_system(
+    mut commands: Commands,
+    mut pathfinding_queue: ResMut<PathfindingQueue>,
+    mut agent_path_tickets: ResMut<AgentPathTickets>,
+    mut finished_events: EventReader<PathfindingFinished>,
+    mut failed_events: EventWriter<PathRequestFailed>,
+) {
+    for finished in finished_events.read() {
+        let Some(entity) = agent_path_tickets.by_ticket.remove(&finished.ticket) else {
+            continue;
+        };
+        agent_path_tickets.by_entity.remove(&entity);
+        // The result is read from the event, drop the copy kept by the queue.
+        pathfinding_queue.take_result(finished.ticket);
+
+        let Ok(mut entity_commands) = commands.get_entity(entity) else {
+            continue;
+        };
+
+        match &finished.result {
+            Ok(path) => {
+                entity_commands
+                    .try_insert(NavPath {
+                        corners: path.points.clone(),
+                        status: path.status,
+                        polygons: finished.polygons.clone(),
+                    })
+                    .try_remove::<PathRequest>();
+            }
+            Err(error) => {
+                entity_commands.try_remove::<(PathRequest, NavPath)>();
+
+                failed_events.write(PathRequestFailed {
+                    entity,
+                    error: error.clone(),
+                });
+            }
+        }
+    }
+}
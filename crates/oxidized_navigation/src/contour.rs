@@ -17,6 +17,7 @@ pub struct Contour {
     pub region: u16,
     /// Unlike [OpenSpan] this can't be ``None`` as ``None`` spans are ignored when generating contours.  
     pub area: Area,
+    pub flags: u16,
 }
 
 #[derive(Default)]
@@ -114,6 +115,7 @@ pub fn build_contours(open_tile: &OpenTile, nav_mesh_settings: &NavMeshSettings)
                     vertices: simplified_vertices.clone(),
                     region: span.region,
                     area,
+                    flags: open_tile.flags[span.tile_index],
                 };
 
                 contour_set.contours.push(new_contour);
@@ -11,6 +11,7 @@ pub struct GeometryCollection {
     pub transform: Transform,
     pub geometry_to_convert: GeometryToConvert,
     pub area: Option<Area>,
+    pub flags: u16,
 }
 
 pub enum ColliderType {
@@ -80,6 +81,7 @@ pub(super) fn convert_geometry_collections(
             transform: geometry_collection.transform,
             triangles: convert_geometry(geometry_collection.geometry_to_convert),
             area: geometry_collection.area,
+            flags: geometry_collection.flags,
         })
         .collect()
 }
@@ -0,0 +1,316 @@
+//! Module for following a polygon path without re-running pathfinding every frame.
+//!
+//! See [PathCorridor].
+
+use bevy::{math::Vec3Swizzles, prelude::Vec3};
+
+use crate::{
+    query::{
+        find_straight_path_limited, move_along_surface, raycast, MoveAlongSurfaceError,
+        NavMeshQuery, QueryFilter, StraightPathOptions, StraightPathPoint, StringPullingError,
+    },
+    tiles::{NavMeshTiles, PolyRef},
+};
+
+/// Corners closer than this to the corridor's position are skipped by [PathCorridor::find_corners].
+const MIN_CORNER_DISTANCE: f32 = 0.01;
+
+/// A polygon path from the agent's position to its target that is kept up to date as either of them moves.
+///
+/// Create one with [PathCorridor::new], fill it with the result of [crate::query::find_polygon_path] using [PathCorridor::set_corridor] & then every frame:
+/// 1. Call [PathCorridor::find_corners] to get the next few corners to steer toward.
+/// 2. Optionally call [PathCorridor::optimize_path_visibility] with the next corner to shortcut the path.
+/// 3. Call [PathCorridor::move_position] with the agent's new position.
+///
+/// Moving the position or target only adjusts the start or end of the corridor, avoiding the cost of pathfinding & the jitter of repeatedly finding slightly different paths.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathCorridor {
+    position: Vec3,
+    target: Vec3,
+    /// Never empty. The first polygon contains ``position`` & the last one ``target``.
+    polygons: Vec<PolyRef>,
+}
+
+impl PathCorridor {
+    /// Creates a corridor where both the position & target are ``position`` on ``polygon``.
+    pub fn new(polygon: PolyRef, position: Vec3) -> Self {
+        Self {
+            position,
+            target: position,
+            polygons: vec![polygon],
+        }
+    }
+
+    /// Resets the corridor so both the position & target are ``position`` on ``polygon``.
+    pub fn reset(&mut self, polygon: PolyRef, position: Vec3) {
+        self.position = position;
+        self.target = position;
+        self.polygons.clear();
+        self.polygons.push(polygon);
+    }
+
+    /// Replaces the corridor with ``polygons``, for example the polygons of a [crate::query::PolygonPath].
+    ///
+    /// The first polygon should contain the current position & the last one ``target``. Does nothing if ``polygons`` is empty.
+    pub fn set_corridor(&mut self, target: Vec3, polygons: &[PolyRef]) {
+        if polygons.is_empty() {
+            return;
+        }
+
+        self.target = target;
+        self.polygons.clear();
+        self.polygons.extend_from_slice(polygons);
+    }
+
+    /// Returns the current position of the corridor.
+    #[inline]
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    /// Returns the current target of the corridor.
+    #[inline]
+    pub fn target(&self) -> Vec3 {
+        self.target
+    }
+
+    /// Returns the polygons of the corridor, from the polygon containing the position to the polygon containing the target.
+    #[inline]
+    pub fn polygons(&self) -> &[PolyRef] {
+        &self.polygons
+    }
+
+    /// Returns the polygon containing the position.
+    #[inline]
+    pub fn first_polygon(&self) -> PolyRef {
+        self.polygons[0]
+    }
+
+    /// Returns the polygon containing the target.
+    #[inline]
+    pub fn last_polygon(&self) -> PolyRef {
+        self.polygons[self.polygons.len() - 1]
+    }
+
+    /// Finds up to ``max_corners`` string pulled corners along the corridor, starting from the current position.
+    ///
+    /// Corners at the current position are skipped, meaning an empty result means the target has been reached. Only the start of the corridor is string pulled, so this stays cheap for long corridors.
+    pub fn find_corners(
+        &self,
+        nav_mesh: &NavMeshTiles,
+        max_corners: usize,
+    ) -> Result<Vec<StraightPathPoint>, StringPullingError> {
+        // +1 as the first point is always the current position.
+        let straight_path = find_straight_path_limited(
+            nav_mesh,
+            self.position,
+            self.target,
+            &self.polygons,
+            StraightPathOptions::default(),
+            max_corners.saturating_add(1),
+        )?;
+
+        let mut corners = straight_path.points;
+        let skip = corners
+            .iter()
+            .take_while(|corner| {
+                corner.position.xz().distance_squared(self.position.xz())
+                    <= MIN_CORNER_DISTANCE * MIN_CORNER_DISTANCE
+            })
+            .count();
+        corners.drain(..skip);
+        corners.truncate(max_corners);
+
+        Ok(corners)
+    }
+
+    /// Attempts to shortcut the start of the corridor by casting a ray from the current position toward ``next``, usually the next corner.
+    ///
+    /// Returns true if the corridor changed.
+    ///
+    /// * ``path_optimization_range`` - How far to cast the ray. Longer rays find more shortcuts but cost more.
+    /// * ``filter`` - Filter deciding which polygons may be traversed.
+    pub fn optimize_path_visibility(
+        &mut self,
+        nav_mesh: &NavMeshTiles,
+        next: Vec3,
+        path_optimization_range: f32,
+        filter: Option<&dyn QueryFilter>,
+    ) -> bool {
+        let distance = self.position.xz().distance(next.xz());
+        if distance < 0.01 {
+            return false;
+        }
+
+        // Extend the ray up to the optimization range to catch shortcuts past ``next``.
+        let distance = (distance + 0.01).min(path_optimization_range);
+        let goal = self.position + (next - self.position) * (path_optimization_range / distance);
+
+        let Ok(result) = raycast(nav_mesh, self.first_polygon(), self.position, goal, filter)
+        else {
+            return false;
+        };
+
+        if result.polygons.len() > 1 && result.hit.is_none_or(|hit| hit.t > 0.99) {
+            return merge_corridor_start_shortcut(&mut self.polygons, &result.polygons);
+        }
+
+        false
+    }
+
+    /// Attempts to find a cheaper route for the start of the corridor by running a small search from the first polygon toward the target.
+    ///
+    /// Returns true if the corridor changed. This is more expensive than [PathCorridor::optimize_path_visibility] & should be called less often, for example every few hundred milliseconds or when the agent's path has been altered.
+    ///
+    /// * ``query`` - Query used for the search. Its current search is discarded.
+    /// * ``max_iterations`` - Maximum amount of polygons to expand.
+    /// * ``filter`` - Filter deciding which polygons may be traversed & at what cost.
+    pub fn optimize_path_topology(
+        &mut self,
+        query: &mut NavMeshQuery,
+        nav_mesh: &NavMeshTiles,
+        max_iterations: usize,
+        filter: Option<&dyn QueryFilter>,
+    ) -> bool {
+        if self.polygons.len() < 3 {
+            return false;
+        }
+
+        let path = query.find_path_to_furthest_existing(
+            nav_mesh,
+            (self.first_polygon(), self.position),
+            (self.last_polygon(), self.target),
+            &self.polygons,
+            max_iterations,
+            filter,
+        );
+
+        merge_corridor_start_shortcut(&mut self.polygons, &path)
+    }
+
+    /// Moves the position of the corridor toward ``new_position`` along the nav-mesh, adjusting the start of the corridor to the polygons visited.
+    ///
+    /// The position is constrained to the nav-mesh, so it may not end up at ``new_position``.
+    ///
+    /// * ``filter`` - Filter deciding which polygons may be traversed.
+    pub fn move_position(
+        &mut self,
+        nav_mesh: &NavMeshTiles,
+        new_position: Vec3,
+        filter: Option<&dyn QueryFilter>,
+    ) -> Result<(), MoveAlongSurfaceError> {
+        let result = move_along_surface(
+            nav_mesh,
+            self.first_polygon(),
+            self.position,
+            new_position,
+            filter,
+        )?;
+
+        merge_corridor_start_moved(&mut self.polygons, &result.polygons);
+        self.position = result.position;
+
+        Ok(())
+    }
+
+    /// Moves the target of the corridor toward ``new_target`` along the nav-mesh, adjusting the end of the corridor to the polygons visited.
+    ///
+    /// Only use this for small movements of the target. For large movements find a new path & call [PathCorridor::set_corridor].
+    ///
+    /// * ``filter`` - Filter deciding which polygons may be traversed.
+    pub fn move_target(
+        &mut self,
+        nav_mesh: &NavMeshTiles,
+        new_target: Vec3,
+        filter: Option<&dyn QueryFilter>,
+    ) -> Result<(), MoveAlongSurfaceError> {
+        let result = move_along_surface(
+            nav_mesh,
+            self.last_polygon(),
+            self.target,
+            new_target,
+            filter,
+        )?;
+
+        merge_corridor_end_moved(&mut self.polygons, &result.polygons);
+        self.target = result.position;
+
+        Ok(())
+    }
+
+    /// Returns true if the first ``max_look_ahead`` polygons of the corridor are still valid (see [NavMeshTiles::is_valid_poly_ref]) & pass ``filter``.
+    ///
+    /// Use this to check if the corridor needs to be replanned after the nav-mesh has changed.
+    pub fn is_valid(
+        &self,
+        nav_mesh: &NavMeshTiles,
+        max_look_ahead: usize,
+        filter: Option<&dyn QueryFilter>,
+    ) -> bool {
+        self.polygons.iter().take(max_look_ahead).all(|poly_ref| {
+            nav_mesh
+                .get_tile_and_polygon(*poly_ref)
+                .is_some_and(|(tile, _)| {
+                    filter.is_none_or(|filter| {
+                        tile.polygon_passes_filter(poly_ref.tile(), poly_ref.polygon(), filter)
+                    })
+                })
+        })
+    }
+}
+
+/// Returns the indices of the furthest polygon along ``path`` that is also in ``visited``, as (index in path, index in visited).
+fn find_furthest_common_polygon(path: &[PolyRef], visited: &[PolyRef]) -> Option<(usize, usize)> {
+    path.iter().enumerate().rev().find_map(|(i, polygon)| {
+        visited
+            .iter()
+            .position(|visited| visited == polygon)
+            .map(|j| (i, j))
+    })
+}
+
+/// Replaces the start of ``path`` with the polygons ``visited`` when moving from the first polygon.
+fn merge_corridor_start_moved(path: &mut Vec<PolyRef>, visited: &[PolyRef]) {
+    let Some((furthest_path, furthest_visited)) = find_furthest_common_polygon(path, visited)
+    else {
+        return;
+    };
+
+    // The visited polygons after the common one are where we moved to, so they come first in reverse.
+    let start = visited[furthest_visited..].iter().rev().copied();
+    path.splice(..=furthest_path, start);
+}
+
+/// Replaces the end of ``path`` with the polygons ``visited`` when moving from the last polygon.
+fn merge_corridor_end_moved(path: &mut Vec<PolyRef>, visited: &[PolyRef]) {
+    let Some((furthest_path, furthest_visited)) =
+        path.iter().enumerate().find_map(|(i, polygon)| {
+            visited
+                .iter()
+                .position(|visited| visited == polygon)
+                .map(|j| (i, j))
+        })
+    else {
+        return;
+    };
+
+    path.truncate(furthest_path + 1);
+    path.extend_from_slice(&visited[furthest_visited + 1..]);
+}
+
+/// Replaces the start of ``path`` with ``visited`` if it leads to a polygon further along ``path``. Returns true if ``path`` changed.
+fn merge_corridor_start_shortcut(path: &mut Vec<PolyRef>, visited: &[PolyRef]) -> bool {
+    let Some((furthest_path, furthest_visited)) = find_furthest_common_polygon(path, visited)
+    else {
+        return false;
+    };
+
+    let shortcut = &visited[..furthest_visited];
+    if shortcut.is_empty() || path[..furthest_path] == *shortcut {
+        return false;
+    }
+
+    path.splice(..furthest_path, shortcut.iter().copied());
+
+    true
+}
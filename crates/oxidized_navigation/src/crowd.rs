@@ -0,0 +1,1116 @@
+//! Module for moving many agents across the nav-mesh without them walking through each other.
+//!
+//! See [Crowd].
+
+use bevy::{
+    math::Vec3Swizzles,
+    platform::collections::HashMap,
+    prelude::{IVec2, Resource, UVec2, Vec2, Vec3},
+};
+
+use crate::{
+    mesher::VERTICES_IN_TRIANGLE,
+    query::{find_polygons_around_circle, move_along_surface, PassAllFilter, Path, QueryFilter},
+    tiles::{tile_and_neighbours, NavMeshTiles, PolyRef},
+    NavMeshSettings,
+};
+
+const RVO_EPSILON: f32 = 0.00001;
+/// Fraction of the wall range an agent may move before its [LocalBoundary] is searched for again.
+const BOUNDARY_UPDATE_FRACTION: f32 = 0.25;
+
+/// Settings of a single agent in a [Crowd].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrowdAgentParams {
+    /// Radius of the agent.
+    pub radius: f32,
+    /// Maximum speed in world units per second.
+    pub max_speed: f32,
+    /// Maximum change in velocity in world units per second squared.
+    pub max_acceleration: f32,
+    /// Other agents closer than this are avoided.
+    pub neighbour_distance: f32,
+    /// Maximum number of other agents to avoid. The closest ones are picked.
+    pub max_neighbours: usize,
+    /// How far ahead in seconds collisions with other agents are avoided. Larger values make agents react sooner but also more timidly.
+    pub time_horizon: f32,
+    /// How far ahead in seconds collisions with walls are avoided. Walls within ``obstacle_time_horizon * max_speed + radius`` are considered.
+    pub obstacle_time_horizon: f32,
+    /// Distance from the end of the path at which the agent stops.
+    pub arrival_distance: f32,
+}
+
+impl Default for CrowdAgentParams {
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            max_speed: 3.5,
+            max_acceleration: 8.0,
+            neighbour_distance: 5.0,
+            max_neighbours: 10,
+            time_horizon: 2.0,
+            obstacle_time_horizon: 1.0,
+            arrival_distance: 0.3,
+        }
+    }
+}
+
+impl CrowdAgentParams {
+    /// Setter for [CrowdAgentParams::radius]
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Setter for [CrowdAgentParams::max_speed]
+    pub fn with_max_speed(mut self, max_speed: f32) -> Self {
+        self.max_speed = max_speed;
+        self
+    }
+
+    /// Setter for [CrowdAgentParams::max_acceleration]
+    pub fn with_max_acceleration(mut self, max_acceleration: f32) -> Self {
+        self.max_acceleration = max_acceleration;
+        self
+    }
+
+    /// Setter for [CrowdAgentParams::neighbour_distance]
+    pub fn with_neighbour_distance(mut self, neighbour_distance: f32) -> Self {
+        self.neighbour_distance = neighbour_distance;
+        self
+    }
+
+    /// Setter for [CrowdAgentParams::max_neighbours]
+    pub fn with_max_neighbours(mut self, max_neighbours: usize) -> Self {
+        self.max_neighbours = max_neighbours;
+        self
+    }
+
+    /// Setter for [CrowdAgentParams::time_horizon]
+    pub fn with_time_horizon(mut self, time_horizon: f32) -> Self {
+        self.time_horizon = time_horizon;
+        self
+    }
+
+    /// Setter for [CrowdAgentParams::obstacle_time_horizon]
+    pub fn with_obstacle_time_horizon(mut self, obstacle_time_horizon: f32) -> Self {
+        self.obstacle_time_horizon = obstacle_time_horizon;
+        self
+    }
+
+    /// Setter for [CrowdAgentParams::arrival_distance]
+    pub fn with_arrival_distance(mut self, arrival_distance: f32) -> Self {
+        self.arrival_distance = arrival_distance;
+        self
+    }
+
+    /// Distance within which walls can be reached within the obstacle time horizon.
+    fn wall_range(&self) -> f32 {
+        self.obstacle_time_horizon * self.max_speed + self.radius
+    }
+}
+
+/// Handle to an agent in a [Crowd].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CrowdAgentId {
+    index: u32,
+    generation: u32,
+}
+
+/// An agent in a [Crowd].
+#[derive(Debug, Clone)]
+pub struct CrowdAgent {
+    /// Settings of the agent, can be changed at any time through [Crowd::get_agent_mut].
+    pub params: CrowdAgentParams,
+    position: Vec3,
+    velocity: Vec3,
+    desired_velocity: Vec3,
+    polygon: PolyRef,
+    /// Corners of the path being followed, see [Crowd::set_path].
+    corners: Vec<Vec3>,
+    /// Index of the corner currently moved toward.
+    corner: usize,
+    /// Walls around the agent. **``None``** until the first update or after [Crowd::invalidate_walls].
+    boundary: Option<LocalBoundary>,
+}
+
+impl CrowdAgent {
+    /// Returns the position of the agent. Always on the nav-mesh.
+    #[inline]
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    /// Returns the velocity the agent moved at during the last update.
+    #[inline]
+    pub fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+
+    /// Returns the velocity the agent wanted to move at during the last update, before avoiding other agents & walls.
+    #[inline]
+    pub fn desired_velocity(&self) -> Vec3 {
+        self.desired_velocity
+    }
+
+    /// Returns the polygon containing the agent.
+    #[inline]
+    pub fn polygon(&self) -> PolyRef {
+        self.polygon
+    }
+
+    /// Returns the corners of the path the agent is following.
+    #[inline]
+    pub fn corners(&self) -> &[Vec3] {
+        &self.corners
+    }
+
+    /// Returns true if the agent has reached the end of its path or has no path.
+    pub fn has_arrived(&self) -> bool {
+        self.corners.last().is_none_or(|end| {
+            self.corner + 1 >= self.corners.len()
+                && self.position.xz().distance(end.xz()) <= self.params.arrival_distance
+        })
+    }
+}
+
+/// Manages many agents on the nav-mesh, moving them along their paths while avoiding each other & walls.
+///
+/// Velocities are picked using optimal reciprocal collision avoidance (ORCA), where every agent takes half the responsibility for avoiding each of its neighbours.
+/// Neighbours are found through a proximity grid & walls through the nav-mesh polygons around the agent. Walls are cached per agent until it has moved far enough or a tile around it is rebuilt. Movement is constrained to the nav-mesh with [move_along_surface].
+///
+/// Add agents with [Crowd::add_agent], give them paths found with [crate::query::find_path] through [Crowd::set_path] & call [Crowd::update] every frame.
+#[derive(Resource, Debug, Clone)]
+pub struct Crowd {
+    agents: Vec<Option<CrowdAgent>>,
+    generations: Vec<u32>,
+    free_indices: Vec<u32>,
+    grid: ProximityGrid,
+}
+
+impl Default for Crowd {
+    fn default() -> Self {
+        Self::new(2.0)
+    }
+}
+
+impl Crowd {
+    /// Creates an empty crowd.
+    ///
+    /// * ``grid_cell_size`` - Size of the cells of the proximity grid used to find neighbouring agents. Around the largest agent diameter works well.
+    pub fn new(grid_cell_size: f32) -> Self {
+        Self {
+            agents: Vec::new(),
+            generations: Vec::new(),
+            free_indices: Vec::new(),
+            grid: ProximityGrid::new(grid_cell_size),
+        }
+    }
+
+    /// Adds an agent at the closest point on the nav-mesh to ``position``.
+    ///
+    /// Returns **``None``** if no polygon is found within ``params.radius * 2.0`` of ``position``.
+    pub fn add_agent(
+        &mut self,
+        nav_mesh: &NavMeshTiles,
+        nav_mesh_settings: &NavMeshSettings,
+        position: Vec3,
+        params: CrowdAgentParams,
+        filter: Option<&dyn QueryFilter>,
+    ) -> Option<CrowdAgentId> {
+        let (polygon, position) = nav_mesh.find_closest_polygon_in_box(
+            nav_mesh_settings,
+            position,
+            params.radius * 2.0,
+            filter,
+        )?;
+
+        let agent = CrowdAgent {
+            params,
+            position,
+            velocity: Vec3::ZERO,
+            desired_velocity: Vec3::ZERO,
+            polygon,
+            corners: Vec::new(),
+            corner: 0,
+            boundary: None,
+        };
+
+        let index = match self.free_indices.pop() {
+            Some(index) => {
+                self.agents[index as usize] = Some(agent);
+                index
+            }
+            None => {
+                self.agents.push(Some(agent));
+                self.generations.push(0);
+                (self.agents.len() - 1) as u32
+            }
+        };
+
+        Some(CrowdAgentId {
+            index,
+            generation: self.generations[index as usize],
+        })
+    }
+
+    /// Removes the agent. Returns the removed agent or **``None``** if ``id`` isn't a valid agent.
+    pub fn remove_agent(&mut self, id: CrowdAgentId) -> Option<CrowdAgent> {
+        self.get_agent(id)?;
+
+        let agent = self.agents[id.index as usize].take();
+        self.generations[id.index as usize] = self.generations[id.index as usize].wrapping_add(1);
+        self.free_indices.push(id.index);
+
+        agent
+    }
+
+    /// Returns the agent. **``None``** if ``id`` isn't a valid agent.
+    pub fn get_agent(&self, id: CrowdAgentId) -> Option<&CrowdAgent> {
+        if self.generations.get(id.index as usize) != Some(&id.generation) {
+            return None;
+        }
+
+        self.agents[id.index as usize].as_ref()
+    }
+
+    /// Returns a mutable reference to the agent, to change its [CrowdAgentParams]. **``None``** if ``id`` isn't a valid agent.
+    pub fn get_agent_mut(&mut self, id: CrowdAgentId) -> Option<&mut CrowdAgent> {
+        if self.generations.get(id.index as usize) != Some(&id.generation) {
+            return None;
+        }
+
+        self.agents[id.index as usize].as_mut()
+    }
+
+    /// Returns an iterator over all agents.
+    pub fn agents(&self) -> impl Iterator<Item = (CrowdAgentId, &CrowdAgent)> {
+        self.agents.iter().enumerate().filter_map(|(index, agent)| {
+            agent.as_ref().map(|agent| {
+                (
+                    CrowdAgentId {
+                        index: index as u32,
+                        generation: self.generations[index],
+                    },
+                    agent,
+                )
+            })
+        })
+    }
+
+    /// Makes the agent follow ``path``. Returns false if ``id`` isn't a valid agent.
+    pub fn set_path(&mut self, id: CrowdAgentId, path: &Path) -> bool {
+        let Some(agent) = self.get_agent_mut(id) else {
+            return false;
+        };
+
+        agent.corners.clear();
+        agent.corners.extend_from_slice(&path.points);
+        agent.corner = 0;
+
+        true
+    }
+
+    /// Stops the agent, clearing its path. Returns false if ``id`` isn't a valid agent.
+    pub fn stop(&mut self, id: CrowdAgentId) -> bool {
+        let Some(agent) = self.get_agent_mut(id) else {
+            return false;
+        };
+
+        agent.corners.clear();
+        agent.corner = 0;
+
+        true
+    }
+
+    /// Makes every agent search for the walls around it again during the next update.
+    ///
+    /// Walls are only searched for again automatically when an agent has moved far enough or a tile around it has been rebuilt. Call this after changing polygon flags with [NavMeshTiles::set_polygon_flags] or the filter passed to [Crowd::update].
+    pub fn invalidate_walls(&mut self) {
+        for agent in self.agents.iter_mut().flatten() {
+            agent.boundary = None;
+        }
+    }
+
+    /// Moves every agent along its path for ``delta_seconds``, avoiding other agents & walls.
+    ///
+    /// * ``filter`` - Filter deciding which polygons may be traversed. Links into polygons that don't pass the filter are treated as walls. If **``None``** is supplied every polygon may be traversed.
+    pub fn update(
+        &mut self,
+        nav_mesh: &NavMeshTiles,
+        nav_mesh_settings: &NavMeshSettings,
+        delta_seconds: f32,
+        filter: Option<&dyn QueryFilter>,
+    ) {
+        if delta_seconds <= 0.0 {
+            return;
+        }
+
+        // Step 1: Find polygons for agents whose tile has been rebuilt, the walls around them & their desired velocities.
+        for agent in self.agents.iter_mut().flatten() {
+            if !nav_mesh.is_valid_poly_ref(agent.polygon) {
+                if let Some((polygon, position)) = nav_mesh.find_closest_polygon_in_box(
+                    nav_mesh_settings,
+                    agent.position,
+                    agent.params.radius * 2.0,
+                    filter,
+                ) {
+                    agent.polygon = polygon;
+                    agent.position = position;
+                }
+            }
+
+            let wall_range = agent.params.wall_range();
+            if agent
+                .boundary
+                .as_ref()
+                .is_none_or(|boundary| boundary.is_outdated(nav_mesh, agent.position, wall_range))
+            {
+                agent.boundary = Some(find_walls(
+                    nav_mesh,
+                    agent.polygon,
+                    agent.position,
+                    wall_range,
+                    filter,
+                ));
+            }
+
+            agent.desired_velocity = calculate_desired_velocity(agent);
+        }
+
+        // Step 2: Rebuild the proximity grid.
+        self.grid.clear();
+        for (index, agent) in self.agents.iter().enumerate() {
+            if let Some(agent) = agent {
+                self.grid.insert(index, agent.position.xz());
+            }
+        }
+
+        // Step 3: Pick new velocities.
+        let mut new_velocities = vec![Vec2::ZERO; self.agents.len()];
+        let mut neighbours = Vec::new();
+        let mut walls = Vec::new();
+        let mut lines = Vec::new();
+        for (index, agent) in self.agents.iter().enumerate() {
+            let Some(agent) = agent else {
+                continue;
+            };
+            let position = agent.position.xz();
+
+            lines.clear();
+
+            // Walls first, they are never relaxed when the problem is infeasible.
+            // Closest first, so walls hidden behind them are skipped as already covered.
+            let wall_range = agent.params.wall_range();
+            walls.clear();
+            walls.extend(
+                agent
+                    .boundary
+                    .iter()
+                    .flat_map(|boundary| boundary.walls.iter())
+                    .filter_map(|(a, b)| {
+                        let distance_squared = distance_squared_to_segment(position, *a, *b);
+
+                        (distance_squared <= wall_range * wall_range).then_some((
+                            distance_squared,
+                            *a,
+                            *b,
+                        ))
+                    }),
+            );
+            walls.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+            for (_, a, b) in walls.iter() {
+                add_wall_line(agent, *a, *b, &mut lines);
+            }
+            let obstacle_line_count = lines.len();
+
+            neighbours.clear();
+            self.grid
+                .query(position, agent.params.neighbour_distance, &mut neighbours);
+            let mut neighbour_distances = neighbours
+                .iter()
+                .filter(|other| **other != index)
+                .filter_map(|other| {
+                    let other_agent = self.agents[*other].as_ref()?;
+                    let distance_squared = position.distance_squared(other_agent.position.xz());
+
+                    (distance_squared
+                        <= agent.params.neighbour_distance * agent.params.neighbour_distance)
+                        .then_some((distance_squared, other_agent))
+                })
+                .collect::<Vec<_>>();
+            neighbour_distances.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+            for (_, other) in neighbour_distances.iter().take(agent.params.max_neighbours) {
+                lines.push(agent_line(agent, other, delta_seconds));
+            }
+
+            let preferred_velocity = agent.desired_velocity.xz();
+            let mut new_velocity = Vec2::ZERO;
+            let line_fail = linear_program_2(
+                &lines,
+                agent.params.max_speed,
+                preferred_velocity,
+                false,
+                &mut new_velocity,
+            );
+            if line_fail < lines.len() {
+                linear_program_3(
+                    &lines,
+                    obstacle_line_count,
+                    line_fail,
+                    agent.params.max_speed,
+                    &mut new_velocity,
+                );
+            }
+
+            // Limit acceleration.
+            let velocity = agent.velocity.xz();
+            let max_change = agent.params.max_acceleration * delta_seconds;
+            new_velocities[index] =
+                velocity + (new_velocity - velocity).clamp_length_max(max_change);
+        }
+
+        // Step 4: Move agents, staying on the nav-mesh.
+        for (agent, new_velocity) in self.agents.iter_mut().zip(new_velocities) {
+            let Some(agent) = agent else {
+                continue;
+            };
+
+            let step = Vec3::new(new_velocity.x, 0.0, new_velocity.y) * delta_seconds;
+            if step.length_squared() <= RVO_EPSILON {
+                agent.velocity = Vec3::ZERO;
+                continue;
+            }
+
+            let Ok(result) = move_along_surface(
+                nav_mesh,
+                agent.polygon,
+                agent.position,
+                agent.position + step,
+                filter,
+            ) else {
+                agent.velocity = Vec3::ZERO;
+                continue;
+            };
+
+            // Use the actual movement so agents don't keep pushing into walls.
+            let moved = result.position - agent.position;
+            agent.velocity = Vec3::new(moved.x, 0.0, moved.z) / delta_seconds;
+            agent.position = result.position;
+            if let Some(polygon) = result.polygons.last() {
+                agent.polygon = *polygon;
+            }
+        }
+    }
+}
+
+/// Advances the agent along its corners & returns the velocity toward the next one.
+fn calculate_desired_velocity(agent: &mut CrowdAgent) -> Vec3 {
+    let Some(end) = agent.corners.last().copied() else {
+        return Vec3::ZERO;
+    };
+
+    let position = agent.position.xz();
+    while agent.corner + 1 < agent.corners.len()
+        && position.distance(agent.corners[agent.corner].xz()) <= agent.params.radius
+    {
+        agent.corner += 1;
+    }
+
+    let remaining_distance = position.distance(agent.corners[agent.corner].xz())
+        + agent.corners[agent.corner..]
+            .windows(2)
+            .map(|pair| pair[0].xz().distance(pair[1].xz()))
+            .sum::<f32>();
+    if agent.corner + 1 >= agent.corners.len()
+        && position.distance(end.xz()) <= agent.params.arrival_distance
+    {
+        return Vec3::ZERO;
+    }
+
+    // Slow down toward the end to stop at it instead of overshooting.
+    let braking_distance = (remaining_distance - agent.params.arrival_distance).max(0.0);
+    let speed = agent
+        .params
+        .max_speed
+        .min((2.0 * agent.params.max_acceleration * braking_distance).sqrt());
+
+    let direction = (agent.corners[agent.corner].xz() - position).normalize_or_zero();
+
+    Vec3::new(direction.x, 0.0, direction.y) * speed
+}
+
+/// Wall edges around an agent, like Detour's ``dtLocalBoundary``.
+///
+/// Searching the nav-mesh for walls is the most expensive part of an update, so it's only done again once the agent has moved [BOUNDARY_UPDATE_FRACTION] of its wall range away from where the walls were searched or a tile the search touched has been rebuilt.
+#[derive(Debug, Clone)]
+struct LocalBoundary {
+    /// Position the walls were searched around.
+    center: Vec3,
+    /// Wall range of the agent when the walls were searched, see [CrowdAgentParams::wall_range].
+    wall_range: f32,
+    walls: Vec<(Vec2, Vec2)>,
+    /// Salts of the tiles the search reached & their neighbours, whose links may have changed walls into open edges.
+    tile_salts: Vec<(UVec2, u32)>,
+}
+
+impl LocalBoundary {
+    /// Returns true if the walls have to be searched for again.
+    fn is_outdated(&self, nav_mesh: &NavMeshTiles, position: Vec3, wall_range: f32) -> bool {
+        self.wall_range != wall_range
+            || position.xz().distance(self.center.xz()) > wall_range * BOUNDARY_UPDATE_FRACTION
+            || self
+                .tile_salts
+                .iter()
+                .any(|(tile_coord, salt)| nav_mesh.get_tile_salt(*tile_coord) != *salt)
+    }
+}
+
+/// Finds the wall edges within reach of an agent at ``position`` with the given wall range.
+///
+/// Searches a little further than ``wall_range`` so the walls stay valid while the agent moves until the boundary is outdated.
+fn find_walls(
+    nav_mesh: &NavMeshTiles,
+    polygon: PolyRef,
+    position: Vec3,
+    wall_range: f32,
+    filter: Option<&dyn QueryFilter>,
+) -> LocalBoundary {
+    let search_range = wall_range * (1.0 + BOUNDARY_UPDATE_FRACTION);
+    let mut boundary = LocalBoundary {
+        center: position,
+        wall_range,
+        walls: Vec::new(),
+        tile_salts: Vec::new(),
+    };
+
+    let Ok(reached) =
+        find_polygons_around_circle(nav_mesh, polygon, position, search_range, filter)
+    else {
+        // Try again next update, the agent's polygon is no longer valid.
+        boundary.tile_salts.push((polygon.tile(), polygon.salt()));
+        return boundary;
+    };
+    let filter = filter.unwrap_or(&PassAllFilter);
+
+    let mut tiles = Vec::new();
+    for reached in reached {
+        tiles.extend(tile_and_neighbours(reached.polygon.tile()));
+
+        let Some((tile, polygon)) = nav_mesh.get_tile_and_polygon(reached.polygon) else {
+            continue;
+        };
+
+        for edge in 0..VERTICES_IN_TRIANGLE as u8 {
+            let is_wall = polygon
+                .links
+                .iter()
+                .filter(|link| link.edge() == edge)
+                .all(|link| {
+                    let neighbour = link.neighbour(reached.polygon.tile());
+                    !nav_mesh.tiles.get(&neighbour.0).is_some_and(|tile| {
+                        tile.polygon_passes_filter(neighbour.0, neighbour.1, filter)
+                    })
+                });
+            if !is_wall {
+                continue;
+            }
+
+            let (a, b) = tile.get_polygon_edge(polygon, edge);
+            let (a, b) = (a.xz(), b.xz());
+            if distance_squared_to_segment(position.xz(), a, b) <= search_range * search_range {
+                boundary.walls.push((a, b));
+            }
+        }
+    }
+
+    tiles.sort_unstable_by_key(|tile_coord| (tile_coord.x, tile_coord.y));
+    tiles.dedup();
+    boundary.tile_salts = tiles
+        .into_iter()
+        .map(|tile_coord| (tile_coord, nav_mesh.get_tile_salt(tile_coord)))
+        .collect();
+
+    boundary
+}
+
+/// Returns the squared distance from ``point`` to the segment from ``a`` to ``b``.
+fn distance_squared_to_segment(point: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let segment = b - a;
+    let t = ((point - a).dot(segment) / segment.length_squared().max(RVO_EPSILON)).clamp(0.0, 1.0);
+
+    point.distance_squared(a + segment * t)
+}
+
+/// A half-plane of permitted velocities. Velocities to the left of ``direction`` through ``point`` are permitted.
+#[derive(Debug, Clone, Copy)]
+struct OrcaLine {
+    point: Vec2,
+    direction: Vec2,
+}
+
+/// Returns the directions of the left & right legs of the velocity obstacle of a vertex at ``relative_position``, touching a circle of ``radius`` around it.
+fn vertex_legs(relative_position: Vec2, radius: f32) -> (Vec2, Vec2) {
+    let distance_squared = relative_position.length_squared();
+    let leg = (distance_squared - radius * radius).max(0.0).sqrt();
+
+    (
+        Vec2::new(
+            relative_position.x * leg - relative_position.y * radius,
+            relative_position.x * radius + relative_position.y * leg,
+        ) / distance_squared,
+        Vec2::new(
+            relative_position.x * leg + relative_position.y * radius,
+            -relative_position.x * radius + relative_position.y * leg,
+        ) / distance_squared,
+    )
+}
+
+/// Adds a line keeping the agent from reaching the wall from ``a`` to ``b`` within its obstacle time horizon.
+///
+/// Same as the obstacle lines of RVO2, with every wall treated as a separate obstacle of two convex vertices. Walls must be added closest first, walls already covered by earlier lines are skipped.
+fn add_wall_line(agent: &CrowdAgent, a: Vec2, b: Vec2, lines: &mut Vec<OrcaLine>) {
+    let position = agent.position.xz();
+    let velocity = agent.velocity.xz();
+    let radius = agent.params.radius;
+    let inverse_time_horizon = 1.0 / agent.params.obstacle_time_horizon;
+
+    // Orient the wall so the agent is on its right, the side RVO2 expects agents on.
+    let (mut vertex_1, mut vertex_2) = if (a - position).perp_dot(b - a) > 0.0 {
+        (b, a)
+    } else {
+        (a, b)
+    };
+    let wall_direction = (vertex_2 - vertex_1).normalize_or_zero();
+    if wall_direction == Vec2::ZERO {
+        return;
+    }
+
+    let relative_position_1 = vertex_1 - position;
+    let relative_position_2 = vertex_2 - position;
+
+    // Skip the wall if its velocity obstacle is already covered by the lines of closer walls.
+    let already_covered = lines.iter().any(|line| {
+        (relative_position_1 * inverse_time_horizon - line.point).perp_dot(line.direction)
+            - inverse_time_horizon * radius
+            >= -RVO_EPSILON
+            && (relative_position_2 * inverse_time_horizon - line.point).perp_dot(line.direction)
+                - inverse_time_horizon * radius
+                >= -RVO_EPSILON
+    });
+    if already_covered {
+        return;
+    }
+
+    let distance_squared_1 = relative_position_1.length_squared();
+    let distance_squared_2 = relative_position_2.length_squared();
+    let radius_squared = radius * radius;
+    let wall = vertex_2 - vertex_1;
+    let s = (-relative_position_1).dot(wall) / wall.length_squared();
+    let distance_squared_line = (-relative_position_1 - wall * s).length_squared();
+
+    // Already colliding, only allow moving away from the wall.
+    if s < 0.0 && distance_squared_1 <= radius_squared {
+        lines.push(OrcaLine {
+            point: Vec2::ZERO,
+            direction: Vec2::new(-relative_position_1.y, relative_position_1.x)
+                .normalize_or(-wall_direction),
+        });
+        return;
+    }
+    if s > 1.0 && distance_squared_2 <= radius_squared {
+        lines.push(OrcaLine {
+            point: Vec2::ZERO,
+            direction: Vec2::new(-relative_position_2.y, relative_position_2.x)
+                .normalize_or(-wall_direction),
+        });
+        return;
+    }
+    if (0.0..1.0).contains(&s) && distance_squared_line <= radius_squared {
+        lines.push(OrcaLine {
+            point: Vec2::ZERO,
+            direction: -wall_direction,
+        });
+        return;
+    }
+
+    // No collision. Find the legs of the velocity obstacle, both come from a single vertex when the wall is viewed obliquely.
+    // The other end of a wall is its neighbour, legs pointing into it are replaced by the wall itself.
+    let (mut left_leg, mut right_leg, left_neighbour_direction, right_direction);
+    if s < 0.0 && distance_squared_line <= radius_squared {
+        vertex_2 = vertex_1;
+        (left_leg, right_leg) = vertex_legs(relative_position_1, radius);
+        left_neighbour_direction = -wall_direction;
+        right_direction = wall_direction;
+    } else if s > 1.0 && distance_squared_line <= radius_squared {
+        vertex_1 = vertex_2;
+        (left_leg, right_leg) = vertex_legs(relative_position_2, radius);
+        left_neighbour_direction = wall_direction;
+        right_direction = -wall_direction;
+    } else {
+        (left_leg, _) = vertex_legs(relative_position_1, radius);
+        (_, right_leg) = vertex_legs(relative_position_2, radius);
+        left_neighbour_direction = -wall_direction;
+        right_direction = -wall_direction;
+    }
+    let single_vertex = vertex_1 == vertex_2;
+
+    let mut is_left_leg_foreign = false;
+    let mut is_right_leg_foreign = false;
+    if left_leg.perp_dot(-left_neighbour_direction) >= 0.0 {
+        left_leg = -left_neighbour_direction;
+        is_left_leg_foreign = true;
+    }
+    if right_leg.perp_dot(right_direction) <= 0.0 {
+        right_leg = right_direction;
+        is_right_leg_foreign = true;
+    }
+
+    let left_cutoff = (vertex_1 - position) * inverse_time_horizon;
+    let right_cutoff = (vertex_2 - position) * inverse_time_horizon;
+    let cutoff = right_cutoff - left_cutoff;
+
+    // Project the current velocity on the velocity obstacle, starting with the cut-off circles.
+    let t = if single_vertex {
+        0.5
+    } else {
+        (velocity - left_cutoff).dot(cutoff) / cutoff.length_squared()
+    };
+    let t_left = (velocity - left_cutoff).dot(left_leg);
+    let t_right = (velocity - right_cutoff).dot(right_leg);
+
+    let cutoff_circle_line = |cutoff_center: Vec2| {
+        let unit_w = (velocity - cutoff_center).normalize_or(-wall_direction.perp());
+        OrcaLine {
+            point: cutoff_center + unit_w * radius * inverse_time_horizon,
+            direction: Vec2::new(unit_w.y, -unit_w.x),
+        }
+    };
+    if (t < 0.0 && t_left < 0.0) || (single_vertex && t_left < 0.0 && t_right < 0.0) {
+        lines.push(cutoff_circle_line(left_cutoff));
+        return;
+    }
+    if t > 1.0 && t_right < 0.0 {
+        lines.push(cutoff_circle_line(right_cutoff));
+        return;
+    }
+
+    // Project on the left leg, the right leg or the cut-off line, whichever is closest to the velocity.
+    let distance_squared_cutoff = if t < 0.0 || t > 1.0 || single_vertex {
+        f32::INFINITY
+    } else {
+        velocity.distance_squared(left_cutoff + cutoff * t)
+    };
+    let distance_squared_left = if t_left < 0.0 {
+        f32::INFINITY
+    } else {
+        velocity.distance_squared(left_cutoff + left_leg * t_left)
+    };
+    let distance_squared_right = if t_right < 0.0 {
+        f32::INFINITY
+    } else {
+        velocity.distance_squared(right_cutoff + right_leg * t_right)
+    };
+
+    let (point, direction) = if distance_squared_cutoff <= distance_squared_left
+        && distance_squared_cutoff <= distance_squared_right
+    {
+        (left_cutoff, -wall_direction)
+    } else if distance_squared_left <= distance_squared_right {
+        // Projecting on a leg replaced by the wall adds no constraint.
+        if is_left_leg_foreign {
+            return;
+        }
+        (left_cutoff, left_leg)
+    } else {
+        if is_right_leg_foreign {
+            return;
+        }
+        (right_cutoff, -right_leg)
+    };
+
+    lines.push(OrcaLine {
+        point: point + Vec2::new(-direction.y, direction.x) * radius * inverse_time_horizon,
+        direction,
+    });
+}
+
+/// Returns the ORCA line for avoiding ``other``.
+fn agent_line(agent: &CrowdAgent, other: &CrowdAgent, delta_seconds: f32) -> OrcaLine {
+    let relative_position = other.position.xz() - agent.position.xz();
+    let relative_velocity = agent.velocity.xz() - other.velocity.xz();
+    let distance_squared = relative_position.length_squared();
+    let combined_radius = agent.params.radius + other.params.radius;
+    let combined_radius_squared = combined_radius * combined_radius;
+
+    let inverse_time_horizon = 1.0 / agent.params.time_horizon;
+
+    let (direction, u) = if distance_squared > combined_radius_squared {
+        // No collision. Vector from cutoff center to relative velocity.
+        let w = relative_velocity - relative_position * inverse_time_horizon;
+        let w_length_squared = w.length_squared();
+        let dot_product_1 = w.dot(relative_position);
+
+        if dot_product_1 < 0.0
+            && dot_product_1 * dot_product_1 > combined_radius_squared * w_length_squared
+        {
+            // Project on the cut-off circle.
+            let w_length = w_length_squared.sqrt();
+            let unit_w = w / w_length;
+
+            (
+                Vec2::new(unit_w.y, -unit_w.x),
+                unit_w * (combined_radius * inverse_time_horizon - w_length),
+            )
+        } else {
+            // Project on the legs.
+            let leg = (distance_squared - combined_radius_squared).sqrt();
+
+            let direction = if relative_position.perp_dot(w) > 0.0 {
+                Vec2::new(
+                    relative_position.x * leg - relative_position.y * combined_radius,
+                    relative_position.x * combined_radius + relative_position.y * leg,
+                ) / distance_squared
+            } else {
+                -Vec2::new(
+                    relative_position.x * leg + relative_position.y * combined_radius,
+                    -relative_position.x * combined_radius + relative_position.y * leg,
+                ) / distance_squared
+            };
+
+            let dot_product_2 = relative_velocity.dot(direction);
+
+            (direction, direction * dot_product_2 - relative_velocity)
+        }
+    } else {
+        // Already colliding. Resolve it within this update.
+        let inverse_time_step = 1.0 / delta_seconds;
+        let w = relative_velocity - relative_position * inverse_time_step;
+        let w_length = w.length();
+        let unit_w = if w_length > RVO_EPSILON {
+            w / w_length
+        } else {
+            // Exactly on top of each other, pick any direction.
+            Vec2::X
+        };
+
+        (
+            Vec2::new(unit_w.y, -unit_w.x),
+            unit_w * (combined_radius * inverse_time_step - w_length),
+        )
+    };
+
+    OrcaLine {
+        point: agent.velocity.xz() + u * 0.5,
+        direction,
+    }
+}
+
+/// Solves a one-dimensional linear program on the line ``line_index``, subject to the lines before it & a circle of ``radius``.
+fn linear_program_1(
+    lines: &[OrcaLine],
+    line_index: usize,
+    radius: f32,
+    optimal_velocity: Vec2,
+    direction_optimal: bool,
+    result: &mut Vec2,
+) -> bool {
+    let line = lines[line_index];
+    let dot_product = line.point.dot(line.direction);
+    let discriminant = dot_product * dot_product + radius * radius - line.point.length_squared();
+
+    if discriminant < 0.0 {
+        // The max speed circle fully invalidates the line.
+        return false;
+    }
+
+    let discriminant_sqrt = discriminant.sqrt();
+    let mut t_left = -dot_product - discriminant_sqrt;
+    let mut t_right = -dot_product + discriminant_sqrt;
+
+    for other in lines[..line_index].iter() {
+        let denominator = line.direction.perp_dot(other.direction);
+        let numerator = other.direction.perp_dot(line.point - other.point);
+
+        if denominator.abs() <= RVO_EPSILON {
+            // The lines are (almost) parallel.
+            if numerator < 0.0 {
+                return false;
+            }
+            continue;
+        }
+
+        let t = numerator / denominator;
+        if denominator >= 0.0 {
+            t_right = t_right.min(t);
+        } else {
+            t_left = t_left.max(t);
+        }
+
+        if t_left > t_right {
+            return false;
+        }
+    }
+
+    *result = if direction_optimal {
+        if optimal_velocity.dot(line.direction) > 0.0 {
+            line.point + line.direction * t_right
+        } else {
+            line.point + line.direction * t_left
+        }
+    } else {
+        let t = line.direction.dot(optimal_velocity - line.point);
+        line.point + line.direction * t.clamp(t_left, t_right)
+    };
+
+    true
+}
+
+/// Solves a two-dimensional linear program subject to ``lines`` & a circle of ``radius``.
+///
+/// Returns the index of the line it failed on, or the number of lines if successful.
+fn linear_program_2(
+    lines: &[OrcaLine],
+    radius: f32,
+    optimal_velocity: Vec2,
+    direction_optimal: bool,
+    result: &mut Vec2,
+) -> usize {
+    *result = if direction_optimal {
+        // ``optimal_velocity`` is a unit direction.
+        optimal_velocity * radius
+    } else if optimal_velocity.length_squared() > radius * radius {
+        optimal_velocity.normalize() * radius
+    } else {
+        optimal_velocity
+    };
+
+    for (i, line) in lines.iter().enumerate() {
+        if line.direction.perp_dot(line.point - *result) > 0.0 {
+            // The result doesn't satisfy this line.
+            let previous_result = *result;
+            if !linear_program_1(
+                lines,
+                i,
+                radius,
+                optimal_velocity,
+                direction_optimal,
+                result,
+            ) {
+                *result = previous_result;
+                return i;
+            }
+        }
+    }
+
+    lines.len()
+}
+
+/// Finds the velocity that least violates the agent lines when no velocity satisfies all of them. Wall lines are never violated.
+fn linear_program_3(
+    lines: &[OrcaLine],
+    obstacle_line_count: usize,
+    begin_line: usize,
+    radius: f32,
+    result: &mut Vec2,
+) {
+    let mut distance = 0.0;
+
+    for i in begin_line..lines.len() {
+        let line = lines[i];
+        if line.direction.perp_dot(line.point - *result) <= distance {
+            continue;
+        }
+
+        // The result violates this line by more than the current distance.
+        let mut projected_lines = lines[..obstacle_line_count].to_vec();
+        for other in lines[obstacle_line_count..i].iter() {
+            let determinant = line.direction.perp_dot(other.direction);
+
+            let point = if determinant.abs() <= RVO_EPSILON {
+                if line.direction.dot(other.direction) > 0.0 {
+                    // The lines point in the same direction.
+                    continue;
+                }
+                // The lines point in opposite directions.
+                (line.point + other.point) * 0.5
+            } else {
+                line.point
+                    + line.direction
+                        * (other.direction.perp_dot(line.point - other.point) / determinant)
+            };
+
+            projected_lines.push(OrcaLine {
+                point,
+                direction: (other.direction - line.direction).normalize_or_zero(),
+            });
+        }
+
+        let previous_result = *result;
+        if linear_program_2(
+            &projected_lines,
+            radius,
+            Vec2::new(-line.direction.y, line.direction.x),
+            true,
+            result,
+        ) < projected_lines.len()
+        {
+            // Can only fail because of floating point errors, keep the previous result.
+            *result = previous_result;
+        }
+
+        distance = line.direction.perp_dot(line.point - *result);
+    }
+}
+
+/// Uniform grid over the XZ-plane used to find agents near a position.
+#[derive(Debug, Clone)]
+struct ProximityGrid {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<usize>>,
+}
+
+impl ProximityGrid {
+    fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: cell_size.max(RVO_EPSILON),
+            cells: HashMap::new(),
+        }
+    }
+
+    fn clear(&mut self) {
+        // Keep the cells used during the last update around for their allocations, dropping the ones that stayed empty.
+        self.cells.retain(|_, items| {
+            let was_used = !items.is_empty();
+            items.clear();
+            was_used
+        });
+    }
+
+    fn cell(&self, position: Vec2) -> IVec2 {
+        (position / self.cell_size).floor().as_ivec2()
+    }
+
+    /// Inserts ``item`` into the cell containing ``position``.
+    fn insert(&mut self, item: usize, position: Vec2) {
+        let cell = self.cell(position);
+        self.cells.entry(cell).or_default().push(item);
+    }
+
+    /// Appends the items positioned in the cells overlapping the circle to ``result``. Every item is in a single cell, so there are no duplicates.
+    fn query(&self, position: Vec2, radius: f32, result: &mut Vec<usize>) {
+        let min = self.cell(position - radius);
+        let max = self.cell(position + radius);
+
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                let Some(items) = self.cells.get(&IVec2::new(x, y)) else {
+                    continue;
+                };
+
+                result.extend_from_slice(items);
+            }
+        }
+    }
+}
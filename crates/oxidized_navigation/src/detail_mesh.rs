@@ -78,6 +78,7 @@ pub fn build_detail_mesh(
         polygons: Vec::with_capacity(poly_mesh.polygons.len()),
         edges: Vec::with_capacity(poly_mesh.edges.len()),
         areas: Vec::with_capacity(poly_mesh.areas.len()),
+        flags: Vec::with_capacity(poly_mesh.flags.len()),
         regions: vec![],
     };
 
@@ -87,12 +88,13 @@ pub fn build_detail_mesh(
     let mut verts = Vec::with_capacity(256);
     let mut queue = Vec::with_capacity(512);
 
-    for (((polygon, (min, max)), region), area) in poly_mesh
+    for ((((polygon, (min, max)), region), area), flags) in poly_mesh
         .polygons
         .iter()
         .zip(polygon_bounds.iter())
         .zip(poly_mesh.regions.iter())
         .zip(poly_mesh.areas.iter())
+        .zip(poly_mesh.flags.iter())
     {
         let vertices = [
             poly_mesh.vertices[polygon[0] as usize],
@@ -156,6 +158,9 @@ pub fn build_detail_mesh(
         high_detail_poly_mesh
             .areas
             .extend([*area].repeat(polygons.len()));
+        high_detail_poly_mesh
+            .flags
+            .extend([*flags].repeat(polygons.len()));
     }
 
     Some(high_detail_poly_mesh)
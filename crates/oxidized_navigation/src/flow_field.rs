@@ -0,0 +1,345 @@
+//! Module for guiding many agents toward the same goal.
+//!
+//! See [compute_flow_field].
+
+use std::collections::BinaryHeap;
+
+use bevy::{
+    math::Vec3Swizzles,
+    platform::collections::{hash_map::Entry, HashMap},
+    prelude::{UVec2, Vec3},
+};
+
+use crate::{
+    query::{OpenListEntry, PassAllFilter, QueryFilter, DEFAULT_POSITION_SEARCH_EXTENTS},
+    tiles::{distance_point_to_segment_2d, Link, NavMeshTiles, PolyRef, ProjectedPoint},
+    Area, NavMeshSettings,
+};
+
+/// Maximum amount of polygons [FlowField::get_direction] steps forward when the position is already on the portal toward the next polygon.
+const MAX_LOOK_AHEAD: usize = 4;
+
+/// A polygon covered by a [FlowField].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlowFieldPolygon {
+    /// Cost of moving from this polygon to the closest goal, as calculated by the filter.
+    pub cost: f32,
+    /// The next polygon toward the closest goal. **``None``** for polygons containing a goal.
+    pub next: Option<PolyRef>,
+}
+
+/// Polygon level flow field returned by [compute_flow_field].
+///
+/// Stores the cost to the closest goal & the next polygon toward it for every polygon reached, letting any number of agents steer toward the goals without pathfinding individually.
+///
+/// The field stays valid until a tile it covers is rebuilt or removed. Check [FlowField::covers_tile] when receiving a [crate::TileGenerated] event, or [FlowField::is_valid] before using it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FlowField {
+    polygons: HashMap<PolyRef, FlowFieldPolygon>,
+    /// Goal position in each goal polygon.
+    goals: HashMap<PolyRef, Vec3>,
+    /// Salt of every covered tile when the field was computed.
+    tile_salts: HashMap<UVec2, u32>,
+}
+
+impl FlowField {
+    /// Returns the cost & next polygon of ``polygon``. **``None``** if the polygon isn't covered by the field.
+    #[inline]
+    pub fn get_polygon(&self, polygon: PolyRef) -> Option<FlowFieldPolygon> {
+        self.polygons.get(&polygon).copied()
+    }
+
+    /// Returns the cost of moving from ``polygon`` to the closest goal. **``None``** if the polygon isn't covered by the field.
+    #[inline]
+    pub fn get_cost(&self, polygon: PolyRef) -> Option<f32> {
+        self.polygons.get(&polygon).map(|polygon| polygon.cost)
+    }
+
+    /// Returns an iterator over every polygon covered by the field.
+    pub fn polygons(&self) -> impl Iterator<Item = (PolyRef, FlowFieldPolygon)> + '_ {
+        self.polygons
+            .iter()
+            .map(|(poly_ref, polygon)| (*poly_ref, *polygon))
+    }
+
+    /// Returns true if the field covers any polygon in the tile at ``tile_coord``.
+    #[inline]
+    pub fn covers_tile(&self, tile_coord: UVec2) -> bool {
+        self.tile_salts.contains_key(&tile_coord)
+    }
+
+    /// Returns true if none of the tiles covered by the field have been rebuilt or removed since it was computed.
+    pub fn is_valid(&self, nav_mesh: &NavMeshTiles) -> bool {
+        self.tile_salts.iter().all(|(tile_coord, salt)| {
+            nav_mesh.tiles.contains_key(tile_coord) && nav_mesh.get_tile_salt(*tile_coord) == *salt
+        })
+    }
+
+    /// Returns the direction on the XZ-plane an agent at ``position`` on ``polygon`` should move in to reach the closest goal.
+    ///
+    /// The direction points toward the portal into the next polygon, or toward the goal in the goal's polygon. Zero if the agent is at the goal.
+    /// Returns **``None``** if the polygon isn't covered by the field.
+    pub fn get_direction(
+        &self,
+        nav_mesh: &NavMeshTiles,
+        polygon: PolyRef,
+        position: Vec3,
+    ) -> Option<Vec3> {
+        let mut current = polygon;
+        let mut target = None;
+        for _ in 0..MAX_LOOK_AHEAD {
+            let Some(next) = self.polygons.get(&current)?.next else {
+                target = self.goals.get(&current).copied();
+                break;
+            };
+
+            let (tile, polygon) = nav_mesh.get_tile_and_polygon(current)?;
+            let link = polygon
+                .links
+                .iter()
+                .find(|link| link.neighbour(current.tile()) == (next.tile(), next.polygon()))?;
+
+            // Aim for the closest point on the portal, keeping away from its ends to not clip corners.
+            let (portal_a, portal_b) = tile.get_link_portal(polygon, link);
+            let (_, t) = distance_point_to_segment_2d(position, portal_a, portal_b);
+            let portal_point = portal_a.lerp(portal_b, t.clamp(0.1, 0.9));
+
+            target = Some(portal_point);
+            // Already on the portal, steer toward the one after it instead.
+            if portal_point.xz().distance_squared(position.xz()) > 0.0001 {
+                break;
+            }
+            current = next;
+        }
+
+        let direction = (target? - position).xz().normalize_or_zero();
+
+        Some(Vec3::new(direction.x, 0.0, direction.y))
+    }
+
+    /// Returns the direction on the XZ-plane an agent at ``position`` should move in to reach the closest goal.
+    ///
+    /// Finds the closest polygon covered by the field & calls [FlowField::get_direction]. Returns **``None``** if no covered polygon is found near ``position``.
+    ///
+    /// * ``position_search_extents`` - Half extents of the box to search for a polygon in. In world units. See [NavMeshTiles::project_point]. If **``None``** is supplied [DEFAULT_POSITION_SEARCH_EXTENTS] is used.
+    pub fn get_direction_at(
+        &self,
+        nav_mesh: &NavMeshTiles,
+        nav_mesh_settings: &NavMeshSettings,
+        position: Vec3,
+        position_search_extents: Option<Vec3>,
+    ) -> Option<Vec3> {
+        let filter = CoveredFilter {
+            nav_mesh,
+            flow_field: self,
+        };
+        let projected = nav_mesh.project_point(
+            nav_mesh_settings,
+            position,
+            position_search_extents.unwrap_or(DEFAULT_POSITION_SEARCH_EXTENTS),
+            Some(&filter),
+        )?;
+
+        self.get_direction(nav_mesh, projected.polygon, projected.position)
+    }
+}
+
+/// Filter only passing polygons covered by a flow field.
+struct CoveredFilter<'a> {
+    nav_mesh: &'a NavMeshTiles,
+    flow_field: &'a FlowField,
+}
+impl QueryFilter for CoveredFilter<'_> {
+    fn pass_filter(&self, tile: UVec2, polygon: u16, _area: Area, _flags: u16) -> bool {
+        self.flow_field
+            .polygons
+            .contains_key(&self.nav_mesh.poly_ref_unchecked(tile, polygon))
+    }
+
+    fn cost(&self, from: Vec3, to: Vec3, _area: Area, _link: Option<&Link>) -> f32 {
+        from.distance(to)
+    }
+}
+
+struct FlowFieldNode {
+    tile: UVec2,
+    polygon: u16,
+    cost: f32,
+    /// Index of the next node toward the closest goal.
+    next: Option<usize>,
+    /// Position the polygon is left at toward ``next``, or the goal position.
+    position: Vec3,
+    closed: bool,
+}
+
+/// Errors returned by [compute_flow_field]
+#[derive(Debug)]
+pub enum ComputeFlowFieldError {
+    /// No polygon found near any of the goals.
+    NoValidGoalPolygon,
+}
+
+/// Computes a [FlowField] toward the closest of ``goals`` using a Dijkstra search out from the goals.
+///
+/// Use this instead of [crate::query::find_path] when many agents are heading to the same goals, as the field is computed once for all of them.
+///
+/// * ``nav_mesh`` - Nav-mesh to compute the field across.
+/// * ``nav_mesh_settings`` - Nav-mesh settings used to generate ``nav_mesh``.
+/// * ``goals`` - Positions to steer toward. Goals without a polygon nearby are ignored.
+/// * ``position_search_extents`` - Half extents of the box to search for a polygon near each goal in. In world units. See [NavMeshTiles::project_point]. If **``None``** is supplied [DEFAULT_POSITION_SEARCH_EXTENTS] is used.
+/// * ``max_cost`` - Polygons costing more than this to reach a goal aren't covered. If **``None``** is supplied every reachable polygon is covered.
+/// * ``filter`` - Filter deciding which polygons may be traversed & at what cost. If **``None``** is supplied every polygon may be traversed & the cost is the distance travelled.
+pub fn compute_flow_field(
+    nav_mesh: &NavMeshTiles,
+    nav_mesh_settings: &NavMeshSettings,
+    goals: &[Vec3],
+    position_search_extents: Option<Vec3>,
+    max_cost: Option<f32>,
+    filter: Option<&dyn QueryFilter>,
+) -> Result<FlowField, ComputeFlowFieldError> {
+    let search_extents = position_search_extents.unwrap_or(DEFAULT_POSITION_SEARCH_EXTENTS);
+    let max_cost = max_cost.unwrap_or(f32::INFINITY);
+
+    let mut nodes = Vec::new();
+    let mut lookup = HashMap::new();
+    let mut open_list = BinaryHeap::new();
+
+    let mut goal_positions = HashMap::new();
+    for goal in goals {
+        let Some(ProjectedPoint {
+            polygon, position, ..
+        }) = nav_mesh.project_point(nav_mesh_settings, *goal, search_extents, filter)
+        else {
+            continue;
+        };
+
+        if let Entry::Vacant(entry) = lookup.entry((polygon.tile(), polygon.polygon())) {
+            entry.insert(nodes.len());
+            open_list.push(OpenListEntry {
+                total_cost: 0.0,
+                node: nodes.len(),
+            });
+            nodes.push(FlowFieldNode {
+                tile: polygon.tile(),
+                polygon: polygon.polygon(),
+                cost: 0.0,
+                next: None,
+                position,
+                closed: false,
+            });
+            goal_positions.insert(polygon, position);
+        }
+    }
+    if nodes.is_empty() {
+        return Err(ComputeFlowFieldError::NoValidGoalPolygon);
+    }
+
+    let filter = filter.unwrap_or(&PassAllFilter);
+
+    while let Some(OpenListEntry { total_cost, node }) = open_list.pop() {
+        let FlowFieldNode {
+            tile: tile_coord,
+            polygon: polygon_index,
+            cost,
+            position,
+            closed,
+            ..
+        } = nodes[node];
+        // Skip stale entries.
+        if closed || total_cost > cost {
+            continue;
+        }
+        nodes[node].closed = true;
+
+        let tile = &nav_mesh.tiles[&tile_coord];
+        let polygon = &tile.polygons[polygon_index as usize];
+
+        for link in polygon.links.iter() {
+            let (neighbour_coord, neighbour_index) = link.neighbour(tile_coord);
+            let Some(neighbour_tile) = nav_mesh.tiles.get(&neighbour_coord) else {
+                continue;
+            };
+            if !neighbour_tile.polygon_passes_filter(neighbour_coord, neighbour_index, filter) {
+                continue;
+            }
+
+            // Agents move from the neighbour into this polygon, so cost the link in that direction.
+            // The segment from the portal to this node's position runs across this polygon & is charged at its area's cost, like in A*.
+            let neighbour_polygon = &neighbour_tile.polygons[neighbour_index as usize];
+            let Some(back_link) = neighbour_polygon
+                .links
+                .iter()
+                .find(|link| link.neighbour(neighbour_coord) == (tile_coord, polygon_index))
+            else {
+                continue;
+            };
+
+            let (portal_a, portal_b) = neighbour_tile.get_link_portal(neighbour_polygon, back_link);
+            let neighbour_position = portal_a.lerp(portal_b, 0.5);
+            let area = tile.areas[polygon_index as usize];
+            let neighbour_cost =
+                cost + filter.cost(neighbour_position, position, area, Some(back_link));
+            if !neighbour_cost.is_finite() || neighbour_cost > max_cost {
+                continue;
+            }
+
+            let neighbour_node = match lookup.entry((neighbour_coord, neighbour_index)) {
+                Entry::Occupied(entry) => {
+                    let neighbour_node = &mut nodes[*entry.get()];
+                    if neighbour_node.closed || neighbour_cost >= neighbour_node.cost {
+                        continue;
+                    }
+
+                    neighbour_node.cost = neighbour_cost;
+                    neighbour_node.next = Some(node);
+                    neighbour_node.position = neighbour_position;
+
+                    *entry.get()
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(nodes.len());
+                    nodes.push(FlowFieldNode {
+                        tile: neighbour_coord,
+                        polygon: neighbour_index,
+                        cost: neighbour_cost,
+                        next: Some(node),
+                        position: neighbour_position,
+                        closed: false,
+                    });
+
+                    nodes.len() - 1
+                }
+            };
+
+            open_list.push(OpenListEntry {
+                total_cost: neighbour_cost,
+                node: neighbour_node,
+            });
+        }
+    }
+
+    let poly_refs = nodes
+        .iter()
+        .map(|node| nav_mesh.poly_ref_unchecked(node.tile, node.polygon))
+        .collect::<Vec<_>>();
+
+    let mut flow_field = FlowField {
+        goals: goal_positions,
+        ..Default::default()
+    };
+    for (poly_ref, node) in poly_refs.iter().zip(nodes.iter()) {
+        flow_field.polygons.insert(
+            *poly_ref,
+            FlowFieldPolygon {
+                cost: node.cost,
+                next: node.next.map(|next| poly_refs[next]),
+            },
+        );
+        flow_field
+            .tile_salts
+            .entry(poly_ref.tile())
+            .or_insert_with(|| nav_mesh.get_tile_salt(poly_ref.tile()));
+    }
+
+    Ok(flow_field)
+}
@@ -14,6 +14,7 @@ struct HeightSpan {
     max: u16,
     traversable: bool,
     area: Option<Area>,
+    flags: u16,
 }
 
 #[derive(Default, Clone)]
@@ -40,6 +41,7 @@ pub(super) struct OpenSpan {
     pub(super) tile_index: usize, // The index of this span in the whole tile.
     pub(super) region: u16,       // Region if non-zero.
     area: Option<Area>, // TODO: Ideally we don't want store this here. It's only here to be copied over to [OpenTile::areas] & bumps up the OpenSpan size from 32b to 40b.
+    flags: u16,         // Same as above but copied over to [OpenTile::flags].
 }
 
 #[derive(Default, Debug)]
@@ -47,6 +49,7 @@ pub struct OpenTile {
     pub(super) cells: Vec<OpenCell>, // len = tiles_along_width^2. Laid out X to Y
     pub(super) distances: Box<[u16]>, // Distances used in watershed. One per span. Use tile_index to go from span to distance.
     pub(super) areas: Box<[Option<Area>]>,
+    pub(super) flags: Box<[u16]>,
     pub(super) max_distance: u16,
     pub(super) span_count: usize, // Total spans in all cells.
     pub(super) max_regions: u16,
@@ -56,12 +59,14 @@ pub(super) struct TriangleCollection {
     pub(super) transform: Transform,
     pub(super) triangles: Triangles,
     pub(super) area: Option<Area>,
+    pub(super) flags: u16,
 }
 
 pub struct HeightFieldCollection {
     pub transform: Transform,
     pub heightfield: HeightField,
     pub area: Option<Area>,
+    pub flags: u16,
 }
 
 pub(super) fn build_heightfield_tile(
@@ -104,6 +109,7 @@ pub(super) fn build_heightfield_tile(
                     tile_side,
                     &mut voxel_tile.cells,
                     collection.area,
+                    collection.flags,
                 );
             }
             Triangles::TriMesh(vertices, triangles) => {
@@ -128,6 +134,7 @@ pub(super) fn build_heightfield_tile(
                         tile_side,
                         &mut voxel_tile.cells,
                         collection.area,
+                        collection.flags,
                     );
                 }
             }
@@ -158,6 +165,7 @@ pub(super) fn build_heightfield_tile(
                 tile_side,
                 &mut voxel_tile.cells,
                 collection.area,
+                collection.flags,
             );
         }
     }
@@ -175,6 +183,7 @@ fn process_triangle(
     tile_side: usize,
     voxel_cells: &mut [VoxelCell],
     area: Option<Area>,
+    flags: u16,
 ) {
     let min_bound = a.min(b).min(c).div(nav_mesh_settings.cell_width).as_ivec3();
     let max_bound = a.max(b).max(c).div(nav_mesh_settings.cell_width).as_ivec3();
@@ -270,6 +279,7 @@ fn process_triangle(
                 max: max_height,
                 traversable,
                 area,
+                flags,
             };
 
             if cell.spans.is_empty() {
@@ -292,11 +302,13 @@ fn process_triangle(
                         Ordering::Greater => {
                             new_span.traversable = existing_span.traversable;
                             new_span.area = existing_span.area;
+                            new_span.flags = existing_span.flags;
                         }
                         Ordering::Equal => {
                             new_span.traversable |= existing_span.traversable;
                             // Higher area number has higher priority.
                             new_span.area = new_span.area.max(existing_span.area);
+                            new_span.flags |= existing_span.flags;
                         }
                         Ordering::Less => {}
                     }
@@ -431,6 +443,7 @@ pub fn build_open_heightfield_tile(
                         min: span.max,
                         max: Some(next_span.min),
                         area,
+                        flags: span.flags,
                         ..Default::default()
                     });
                 }
@@ -440,6 +453,7 @@ pub fn build_open_heightfield_tile(
                     min: span.max,
                     max: None,
                     area,
+                    flags: span.flags,
                     ..Default::default()
                 });
             }
@@ -452,18 +466,20 @@ pub fn build_open_heightfield_tile(
         cells,
         distances: vec![u16::MAX; span_count].into_boxed_slice(),
         areas: vec![None; span_count].into_boxed_slice(),
+        flags: vec![0; span_count].into_boxed_slice(),
         max_distance: 0,
         span_count,
         max_regions: 0,
     };
 
-    // Assign tile_index & copy over areas.
+    // Assign tile_index & copy over areas & flags.
     let mut tile_index = 0;
     for cell in open_tile.cells.iter_mut() {
         for span in cell.spans.iter_mut() {
             span.tile_index = tile_index;
 
             open_tile.areas[tile_index] = span.area;
+            open_tile.flags[tile_index] = span.flags;
 
             tile_index += 1;
         }
@@ -582,13 +598,16 @@ pub fn calculate_distance_field(open_tile: &mut OpenTile, nav_mesh_settings: &Na
     for (i, cell) in open_tile.cells.iter().enumerate() {
         for span in cell.spans.iter() {
             let area = open_tile.areas[span.tile_index];
+            let flags = open_tile.flags[span.tile_index];
 
             let all_neighbours = span.neighbours.iter().enumerate().all(|(dir, neighbour)| {
                 if let Some(neighbour) = neighbour {
                     let neighbour_index = get_neighbour_index(tile_side, i, dir);
                     let neighbour = &open_tile.cells[neighbour_index].spans[*neighbour as usize];
 
-                    open_tile.areas[neighbour.tile_index] == area // Only neighbours of same area.
+                    // Only neighbours of same area & flags.
+                    open_tile.areas[neighbour.tile_index] == area
+                        && open_tile.flags[neighbour.tile_index] == flags
                 } else {
                     false
                 }
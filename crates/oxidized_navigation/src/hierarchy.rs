@@ -0,0 +1,762 @@
+//! Module for the abstract tile graph used by hierarchical pathfinding.
+//!
+//! See [crate::query::find_polygon_path_hierarchical].
+
+use std::collections::BinaryHeap;
+
+use bevy::{
+    platform::collections::{hash_map::Entry, HashMap},
+    prelude::{UVec2, Vec3},
+};
+use smallvec::SmallVec;
+
+use crate::{
+    mesher::EdgeConnectionDirection,
+    query::{OpenListEntry, PassAllFilter, QueryFilter, HEURISTIC_SCALE},
+    tiles::{Link, NavMeshTile, NavMeshTiles, PolyRef},
+    Area,
+};
+
+/// Border edges whose end points are closer than this are considered connected when clustering.
+const CLUSTER_DISTANCE: f32 = 0.01;
+
+const DIRECTIONS: [EdgeConnectionDirection; 4] = [
+    EdgeConnectionDirection::XNegative,
+    EdgeConnectionDirection::ZPositive,
+    EdgeConnectionDirection::XPositive,
+    EdgeConnectionDirection::ZNegative,
+];
+
+/// A cluster of connected polygons along one side of a tile that link to the neighbouring tile. Node in the [AbstractGraph].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbstractNode {
+    /// Side of the tile the cluster lies on.
+    pub direction: EdgeConnectionDirection,
+    /// Polygons in the cluster.
+    pub polygons: Vec<u16>,
+    /// Polygon representing the cluster. Paths are refined to & from this polygon.
+    pub polygon: u16,
+    /// Middle of the representative polygon's border edge.
+    pub position: Vec3,
+    /// Precomputed moves to the other nodes in the same tile, ignoring any [QueryFilter]. Nodes that can't be reached within the tile are left out.
+    pub edges: Vec<AbstractEdge>,
+}
+
+/// Precomputed move between two [AbstractNode]s of the same tile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbstractEdge {
+    /// Index of the node moved to.
+    pub node: usize,
+    /// Distance travelled.
+    pub cost: f32,
+    /// Distance travelled across each area type as (area, distance).
+    pub area_distances: SmallVec<[(Area, f32); 2]>,
+}
+
+/// The [AbstractNode]s of a single tile.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AbstractTile {
+    pub nodes: Vec<AbstractNode>,
+    /// Index of the node each border polygon belongs to, by polygon index & side of the tile.
+    polygon_nodes: HashMap<(u16, EdgeConnectionDirection), usize>,
+}
+
+impl AbstractTile {
+    fn index_polygons(&mut self) {
+        self.polygon_nodes.clear();
+        for (node_index, node) in self.nodes.iter().enumerate() {
+            for polygon in node.polygons.iter() {
+                self.polygon_nodes
+                    .insert((*polygon, node.direction), node_index);
+            }
+        }
+    }
+}
+
+/// Graph of polygon clusters along tile borders, used to find long paths without searching every polygon in between.
+///
+/// Kept up to date by [NavMeshTiles::add_tile] & [NavMeshTiles::remove_tile]. The precomputed intra-tile costs are the distance travelled, searches with a [QueryFilter] charge it at the filter's area costs.
+/// Only tiles where the filter excludes polygons are searched again, so flags & excluded areas are respected everywhere.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AbstractGraph {
+    tiles: HashMap<UVec2, AbstractTile>,
+}
+
+impl AbstractGraph {
+    /// Returns a [HashMap] containing the abstract nodes of every tile.
+    pub fn get_tiles(&self) -> &HashMap<UVec2, AbstractTile> {
+        &self.tiles
+    }
+
+    /// Rebuilds the nodes of the tile at ``tile_coord``. Removes them if ``tile`` is **``None``**.
+    pub(crate) fn rebuild_tile(&mut self, tile_coord: UVec2, tile: Option<&NavMeshTile>) {
+        match tile {
+            Some(tile) => {
+                self.tiles
+                    .insert(tile_coord, build_abstract_tile(tile, tile_coord));
+            }
+            None => {
+                self.tiles.remove(&tile_coord);
+            }
+        }
+    }
+
+    /// Rebuilds the nodes on the ``direction`` side of the tile at ``tile_coord`` & the moves to & from them, keeping the tile's other nodes.
+    ///
+    /// Used when only the links to the neighbour on that side have changed. Moves within a tile cost the same both ways, so the moves to the new nodes are taken from the new nodes' searches.
+    pub(crate) fn rebuild_tile_side(
+        &mut self,
+        tile_coord: UVec2,
+        tile: &NavMeshTile,
+        direction: EdgeConnectionDirection,
+    ) {
+        let Some(abstract_tile) = self.tiles.get_mut(&tile_coord) else {
+            self.tiles
+                .insert(tile_coord, build_abstract_tile(tile, tile_coord));
+            return;
+        };
+
+        // Drop the nodes on this side & the moves to them, shifting the indices of the rest.
+        let mut new_indices = Vec::with_capacity(abstract_tile.nodes.len());
+        let mut kept_count = 0;
+        for node in abstract_tile.nodes.iter() {
+            if node.direction == direction {
+                new_indices.push(None);
+            } else {
+                new_indices.push(Some(kept_count));
+                kept_count += 1;
+            }
+        }
+        abstract_tile
+            .nodes
+            .retain(|node| node.direction != direction);
+        for node in abstract_tile.nodes.iter_mut() {
+            node.edges.retain_mut(|edge| match new_indices[edge.node] {
+                Some(index) => {
+                    edge.node = index;
+                    true
+                }
+                None => false,
+            });
+        }
+
+        abstract_tile
+            .nodes
+            .extend(build_side_nodes(tile, direction));
+        abstract_tile.index_polygons();
+
+        let representatives =
+            filtered_representatives(tile, tile_coord, abstract_tile, &PassAllFilter);
+        for i in kept_count..abstract_tile.nodes.len() {
+            let edges = compute_node_edges(tile, tile_coord, &representatives, i, &PassAllFilter);
+            for edge in edges.iter().filter(|edge| edge.node < kept_count) {
+                abstract_tile.nodes[edge.node].edges.push(AbstractEdge {
+                    node: i,
+                    ..edge.clone()
+                });
+            }
+            abstract_tile.nodes[i].edges = edges;
+        }
+    }
+}
+
+fn build_abstract_tile(tile: &NavMeshTile, tile_coord: UVec2) -> AbstractTile {
+    let mut abstract_tile = AbstractTile {
+        nodes: DIRECTIONS
+            .iter()
+            .flat_map(|direction| build_side_nodes(tile, *direction))
+            .collect(),
+        ..Default::default()
+    };
+    abstract_tile.index_polygons();
+
+    // Precompute the cost between every pair of nodes within the tile.
+    let representatives =
+        filtered_representatives(tile, tile_coord, &abstract_tile, &PassAllFilter);
+    for i in 0..abstract_tile.nodes.len() {
+        abstract_tile.nodes[i].edges =
+            compute_node_edges(tile, tile_coord, &representatives, i, &PassAllFilter);
+    }
+
+    abstract_tile
+}
+
+/// Returns the nodes on the ``direction`` side of ``tile``, one per cluster of border edges linking to the neighbour on that side. Their edges are left empty.
+fn build_side_nodes(tile: &NavMeshTile, direction: EdgeConnectionDirection) -> Vec<AbstractNode> {
+    // Border edges linking to the neighbour on this side as (polygon, edge).
+    let mut border_edges = Vec::new();
+    for (polygon_index, polygon) in tile.polygons.iter().enumerate() {
+        for link in polygon.links.iter() {
+            if let Link::External {
+                edge,
+                direction: link_direction,
+                ..
+            } = link
+            {
+                if *link_direction == direction {
+                    border_edges.push((polygon_index as u16, *edge));
+                }
+            }
+        }
+    }
+    // An edge links to every neighbouring polygon it touches.
+    border_edges.sort_unstable();
+    border_edges.dedup();
+
+    // Border edges as (polygon, edge start, edge end).
+    let edges = border_edges
+        .iter()
+        .map(|(polygon, edge)| {
+            let (a, b) = tile.get_polygon_edge(&tile.polygons[*polygon as usize], *edge);
+            (*polygon, a, b)
+        })
+        .collect::<Vec<_>>();
+
+    // Cluster edges sharing end points.
+    let mut clusters = (0..edges.len()).collect::<Vec<_>>();
+    fn find(clusters: &mut [usize], mut i: usize) -> usize {
+        while clusters[i] != i {
+            clusters[i] = clusters[clusters[i]];
+            i = clusters[i];
+        }
+        i
+    }
+    // Every edge lies along the same side of the tile, so sorting the end points along it puts shared ones next to each other.
+    let along_side = |point: Vec3| match direction {
+        EdgeConnectionDirection::XNegative | EdgeConnectionDirection::XPositive => point.z,
+        EdgeConnectionDirection::ZPositive | EdgeConnectionDirection::ZNegative => point.x,
+    };
+    let mut end_points = edges
+        .iter()
+        .enumerate()
+        .flat_map(|(i, (_, a, b))| [(along_side(*a), *a, i), (along_side(*b), *b, i)])
+        .collect::<Vec<_>>();
+    end_points.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+    for i in 0..end_points.len() {
+        let (offset, point, edge) = end_points[i];
+        for (other_offset, other_point, other_edge) in end_points[i + 1..].iter() {
+            if other_offset - offset >= CLUSTER_DISTANCE {
+                break;
+            }
+            if point.distance_squared(*other_point) < CLUSTER_DISTANCE * CLUSTER_DISTANCE {
+                let root = find(&mut clusters, edge);
+                let other_root = find(&mut clusters, *other_edge);
+                clusters[root] = other_root;
+            }
+        }
+    }
+
+    let mut cluster_edges: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..edges.len() {
+        let root = find(&mut clusters, i);
+        cluster_edges.entry(root).or_default().push(i);
+    }
+
+    let mut cluster_edges = cluster_edges.into_values().collect::<Vec<_>>();
+    // Keep node order stable between rebuilds of the same tile.
+    cluster_edges.sort_unstable_by_key(|edge_indices| edge_indices[0]);
+
+    cluster_edges
+        .into_iter()
+        .map(|edge_indices| {
+            let midpoint = |i: usize| edges[i].1.lerp(edges[i].2, 0.5);
+            let center =
+                edge_indices.iter().map(|i| midpoint(*i)).sum::<Vec3>() / edge_indices.len() as f32;
+            let representative = *edge_indices
+                .iter()
+                .min_by(|a, b| {
+                    midpoint(**a)
+                        .distance_squared(center)
+                        .total_cmp(&midpoint(**b).distance_squared(center))
+                })
+                .unwrap();
+
+            let mut polygons = edge_indices.iter().map(|i| edges[*i].0).collect::<Vec<_>>();
+            polygons.sort_unstable();
+            polygons.dedup();
+
+            AbstractNode {
+                direction,
+                polygons,
+                polygon: edges[representative].0,
+                position: midpoint(representative),
+                edges: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// Returns the polygon & position paths are refined to & from for every node in ``abstract_tile`` when searching with ``filter``.
+///
+/// That's the node's representative if it passes ``filter``, otherwise the passing polygon of the cluster whose border edge is closest to it. **``None``** for nodes without any passing polygon.
+fn filtered_representatives(
+    tile: &NavMeshTile,
+    tile_coord: UVec2,
+    abstract_tile: &AbstractTile,
+    filter: &dyn QueryFilter,
+) -> Vec<Option<(u16, Vec3)>> {
+    abstract_tile
+        .nodes
+        .iter()
+        .map(|node| {
+            if tile.polygon_passes_filter(tile_coord, node.polygon, filter) {
+                return Some((node.polygon, node.position));
+            }
+
+            node.polygons
+                .iter()
+                .filter(|polygon| tile.polygon_passes_filter(tile_coord, **polygon, filter))
+                .filter_map(|polygon| {
+                    let polygon_data = &tile.polygons[*polygon as usize];
+                    polygon_data.links.iter().find_map(|link| match link {
+                        Link::External {
+                            edge, direction, ..
+                        } if *direction == node.direction => {
+                            let (a, b) = tile.get_polygon_edge(polygon_data, *edge);
+                            Some((*polygon, a.lerp(b, 0.5)))
+                        }
+                        _ => None,
+                    })
+                })
+                .min_by(|(_, a), (_, b)| {
+                    a.distance_squared(node.position)
+                        .total_cmp(&b.distance_squared(node.position))
+                })
+        })
+        .collect()
+}
+
+/// Returns the moves from node ``i`` to the other nodes within ``tile``.
+///
+/// * ``representatives`` - Polygon & position of every node in the tile. See [filtered_representatives].
+fn compute_node_edges(
+    tile: &NavMeshTile,
+    tile_coord: UVec2,
+    representatives: &[Option<(u16, Vec3)>],
+    i: usize,
+    filter: &dyn QueryFilter,
+) -> Vec<AbstractEdge> {
+    let Some((polygon, position)) = representatives[i] else {
+        return Vec::new();
+    };
+    let reached = search_tile(tile, tile_coord, polygon, position, filter);
+
+    representatives
+        .iter()
+        .enumerate()
+        .filter(|(j, _)| *j != i)
+        .filter_map(|(j, other)| {
+            let (other_polygon, other_position) = (*other)?;
+            let other_reached = reached[other_polygon as usize]?;
+            let area = tile.areas[other_polygon as usize];
+            let cost = other_reached.cost
+                + filter.cost(other_reached.position, other_position, area, None);
+            if !cost.is_finite() {
+                return None;
+            }
+
+            // Walk back to the start, each step crosses the polygon it leaves.
+            let mut area_distances: SmallVec<[(Area, f32); 2]> = SmallVec::new();
+            let mut add_distance = |area: Area, distance: f32| {
+                if distance <= 0.0 {
+                    return;
+                }
+                match area_distances.iter_mut().find(|(other, _)| *other == area) {
+                    Some((_, total)) => *total += distance,
+                    None => area_distances.push((area, distance)),
+                }
+            };
+            add_distance(area, other_reached.position.distance(other_position));
+            let mut current = other_reached;
+            while let Some(parent) = current.parent {
+                let parent_reached = reached[parent as usize]?;
+                add_distance(
+                    tile.areas[parent as usize],
+                    parent_reached.position.distance(current.position),
+                );
+                current = parent_reached;
+            }
+
+            Some(AbstractEdge {
+                node: j,
+                cost,
+                area_distances,
+            })
+        })
+        .collect()
+}
+
+/// Estimates the cost of ``edge`` with ``filter`` without searching the tile again, charging the distance travelled across each area at the filter's cost of moving from ``from`` toward ``to`` across that area.
+fn estimate_edge_cost(edge: &AbstractEdge, from: Vec3, to: Vec3, filter: &dyn QueryFilter) -> f32 {
+    let step = from + (to - from).normalize_or(Vec3::X);
+
+    edge.area_distances
+        .iter()
+        .map(|(area, distance)| filter.cost(from, step, *area, None) * distance)
+        .sum()
+}
+
+/// Polygon reached by [search_tile].
+#[derive(Debug, Clone, Copy)]
+struct TileSearchNode {
+    /// Cost of reaching the polygon.
+    cost: f32,
+    /// Position the polygon was entered at.
+    position: Vec3,
+    /// Polygon it was entered from. **``None``** for the start polygon.
+    parent: Option<u16>,
+}
+
+/// Dijkstra search from ``start_polygon`` staying within ``tile``.
+///
+/// Returns how every polygon in the tile was reached. **``None``** for polygons that can't be reached.
+fn search_tile(
+    tile: &NavMeshTile,
+    tile_coord: UVec2,
+    start_polygon: u16,
+    start_pos: Vec3,
+    filter: &dyn QueryFilter,
+) -> Vec<Option<TileSearchNode>> {
+    let mut reached = vec![None; tile.polygons.len()];
+    if !tile.polygon_passes_filter(tile_coord, start_polygon, filter) {
+        return reached;
+    }
+    let mut closed = vec![false; tile.polygons.len()];
+    reached[start_polygon as usize] = Some(TileSearchNode {
+        cost: 0.0,
+        position: start_pos,
+        parent: None,
+    });
+
+    let mut open_list = BinaryHeap::new();
+    open_list.push(OpenListEntry {
+        total_cost: 0.0,
+        node: start_polygon as usize,
+    });
+
+    while let Some(OpenListEntry { total_cost, node }) = open_list.pop() {
+        let Some(TileSearchNode { cost, position, .. }) = reached[node] else {
+            continue;
+        };
+        // Skip stale entries.
+        if closed[node] || total_cost > cost {
+            continue;
+        }
+        closed[node] = true;
+
+        let polygon = &tile.polygons[node];
+        let area = tile.areas[node];
+        for link in polygon.links.iter() {
+            let Link::Internal {
+                neighbour_polygon, ..
+            } = link
+            else {
+                continue;
+            };
+            let neighbour = *neighbour_polygon as usize;
+            if closed[neighbour]
+                || !tile.polygon_passes_filter(tile_coord, *neighbour_polygon, filter)
+            {
+                continue;
+            }
+
+            let (portal_a, portal_b) = tile.get_link_portal(polygon, link);
+            let neighbour_position = portal_a.lerp(portal_b, 0.5);
+            let neighbour_cost = cost + filter.cost(position, neighbour_position, area, Some(link));
+            if !neighbour_cost.is_finite() {
+                continue;
+            }
+
+            if reached[neighbour]
+                .is_some_and(|previous: TileSearchNode| previous.cost <= neighbour_cost)
+            {
+                continue;
+            }
+            reached[neighbour] = Some(TileSearchNode {
+                cost: neighbour_cost,
+                position: neighbour_position,
+                parent: Some(node as u16),
+            });
+            open_list.push(OpenListEntry {
+                total_cost: neighbour_cost,
+                node: neighbour,
+            });
+        }
+    }
+
+    reached
+}
+
+/// Node in the abstract search. ``None`` key is the end position.
+struct SearchNode {
+    key: Option<(UVec2, usize)>,
+    cost: f32,
+    total_cost: f32,
+    parent: Option<usize>,
+    closed: bool,
+}
+
+/// Searches the [AbstractGraph] for the cheapest way from ``start`` to ``end``.
+///
+/// Returns the representative polygon & position of every abstract node passed, in order. **``None``** if no path is found or both are in the same tile.
+///
+/// * ``filter`` - If **``None``** the precomputed intra-tile costs are used. Otherwise they're charged at the filter's area costs, see [AbstractGraph].
+pub(crate) fn find_abstract_path(
+    nav_mesh: &NavMeshTiles,
+    (start, start_pos): (PolyRef, Vec3),
+    (end, end_pos): (PolyRef, Vec3),
+    query_filter: Option<&dyn QueryFilter>,
+) -> Option<Vec<(PolyRef, Vec3)>> {
+    if start.tile() == end.tile() {
+        return None;
+    }
+    let filter = query_filter.unwrap_or(&PassAllFilter);
+    let graph = nav_mesh.abstract_graph();
+    // Node representatives passing ``filter`` by tile, filled in as tiles are reached.
+    let mut representatives = HashMap::new();
+    fn get_representatives<'a>(
+        representatives: &'a mut HashMap<UVec2, Vec<Option<(u16, Vec3)>>>,
+        nav_mesh: &NavMeshTiles,
+        tile_coord: UVec2,
+        filter: &dyn QueryFilter,
+    ) -> &'a [Option<(u16, Vec3)>] {
+        representatives.entry(tile_coord).or_insert_with(|| {
+            filtered_representatives(
+                &nav_mesh.tiles[&tile_coord],
+                tile_coord,
+                &nav_mesh.abstract_graph().tiles[&tile_coord],
+                filter,
+            )
+        })
+    }
+    // Whether ``filter`` excludes any polygon by tile. Only the moves within these tiles are searched again.
+    let mut excluding_tiles: HashMap<UVec2, bool> = HashMap::new();
+    // Intra-tile costs searched again with ``filter`` by (tile, node).
+    let mut filtered_edges: HashMap<(UVec2, usize), Vec<(usize, f32)>> = HashMap::new();
+
+    graph.tiles.get(&start.tile())?;
+    graph.tiles.get(&end.tile())?;
+    let start_tile = nav_mesh.tiles.get(&start.tile())?;
+    let end_tile = nav_mesh.tiles.get(&end.tile())?;
+
+    let start_reached = search_tile(start_tile, start.tile(), start.polygon(), start_pos, filter);
+    // Costs are searched outward from the end, which is close enough for picking the exit node.
+    let end_reached = search_tile(end_tile, end.tile(), end.polygon(), end_pos, filter);
+    let end_representatives =
+        get_representatives(&mut representatives, nav_mesh, end.tile(), filter).to_vec();
+    let cost_to_end = |node_index: usize| {
+        let (polygon, node_position) = end_representatives[node_index]?;
+        let reached = end_reached[polygon as usize]?;
+        let area = end_tile.areas[polygon as usize];
+        let cost = reached.cost + filter.cost(node_position, reached.position, area, None);
+
+        cost.is_finite().then_some(cost)
+    };
+
+    let mut nodes = Vec::new();
+    let mut lookup = HashMap::new();
+    let mut open_list = BinaryHeap::new();
+
+    for (i, representative) in
+        get_representatives(&mut representatives, nav_mesh, start.tile(), filter)
+            .iter()
+            .enumerate()
+    {
+        let Some((polygon, node_position)) = *representative else {
+            continue;
+        };
+        let Some(reached) = start_reached[polygon as usize] else {
+            continue;
+        };
+        let area = start_tile.areas[polygon as usize];
+        let cost = reached.cost + filter.cost(reached.position, node_position, area, None);
+        if !cost.is_finite() {
+            continue;
+        }
+        let total_cost = cost + node_position.distance(end_pos) * HEURISTIC_SCALE;
+
+        lookup.insert(Some((start.tile(), i)), nodes.len());
+        open_list.push(OpenListEntry {
+            total_cost,
+            node: nodes.len(),
+        });
+        nodes.push(SearchNode {
+            key: Some((start.tile(), i)),
+            cost,
+            total_cost,
+            parent: None,
+            closed: false,
+        });
+    }
+
+    let mut end_node = None;
+    while let Some(OpenListEntry { total_cost, node }) = open_list.pop() {
+        let search_node = &mut nodes[node];
+        // Skip stale entries.
+        if search_node.closed || total_cost > search_node.total_cost {
+            continue;
+        }
+        search_node.closed = true;
+
+        let Some((tile_coord, node_index)) = search_node.key else {
+            end_node = Some(node);
+            break;
+        };
+        let cost = search_node.cost;
+
+        let abstract_tile = &graph.tiles[&tile_coord];
+        let abstract_node = &abstract_tile.nodes[node_index];
+        let tile = &nav_mesh.tiles[&tile_coord];
+        let tile_representatives =
+            get_representatives(&mut representatives, nav_mesh, tile_coord, filter);
+        let Some((_, node_position)) = tile_representatives[node_index] else {
+            continue;
+        };
+
+        // Neighbours as (key, position, cost of moving there).
+        let mut neighbours = Vec::new();
+        if tile_coord == end.tile() {
+            if let Some(end_cost) = cost_to_end(node_index) {
+                neighbours.push((None, end_pos, end_cost));
+            }
+        }
+        let excludes_polygons = query_filter.is_some()
+            && *excluding_tiles.entry(tile_coord).or_insert_with(|| {
+                (0..tile.polygons.len() as u16)
+                    .any(|polygon| !tile.polygon_passes_filter(tile_coord, polygon, filter))
+            });
+        if excludes_polygons {
+            let edges = filtered_edges
+                .entry((tile_coord, node_index))
+                .or_insert_with(|| {
+                    compute_node_edges(tile, tile_coord, tile_representatives, node_index, filter)
+                        .into_iter()
+                        .map(|edge| (edge.node, edge.cost))
+                        .collect()
+                });
+            for (other, edge_cost) in edges.iter() {
+                let Some((_, other_position)) = tile_representatives[*other] else {
+                    continue;
+                };
+                neighbours.push((Some((tile_coord, *other)), other_position, *edge_cost));
+            }
+        } else {
+            // Every polygon passes, so the representatives are the precomputed ones.
+            for edge in abstract_node.edges.iter() {
+                let other_position = abstract_tile.nodes[edge.node].position;
+                let edge_cost = match query_filter {
+                    Some(filter) => estimate_edge_cost(edge, node_position, other_position, filter),
+                    None => edge.cost,
+                };
+                if edge_cost.is_finite() {
+                    neighbours.push((Some((tile_coord, edge.node)), other_position, edge_cost));
+                }
+            }
+        }
+        for polygon in abstract_node.polygons.iter() {
+            // Every polygon of the cluster is checked, the border may only be crossable through some of them.
+            if !tile.polygon_passes_filter(tile_coord, *polygon, filter) {
+                continue;
+            }
+            let area = tile.areas[*polygon as usize];
+
+            for link in tile.polygons[*polygon as usize].links.iter() {
+                if !matches!(link, Link::External { direction, .. } if *direction == abstract_node.direction)
+                {
+                    continue;
+                }
+
+                let (neighbour_coord, neighbour_polygon) = link.neighbour(tile_coord);
+                let Some(neighbour_index) = graph
+                    .tiles
+                    .get(&neighbour_coord)
+                    .and_then(|neighbour_tile| {
+                        neighbour_tile
+                            .polygon_nodes
+                            .get(&(neighbour_polygon, abstract_node.direction.opposite()))
+                    })
+                    .copied()
+                else {
+                    continue;
+                };
+
+                // The polygon the border is crossed into must be traversable, as must the one paths are refined to.
+                let passes_filter = nav_mesh.tiles.get(&neighbour_coord).is_some_and(|tile| {
+                    tile.polygon_passes_filter(neighbour_coord, neighbour_polygon, filter)
+                });
+                if !passes_filter {
+                    continue;
+                }
+                let Some((_, position)) =
+                    get_representatives(&mut representatives, nav_mesh, neighbour_coord, filter)
+                        [neighbour_index]
+                else {
+                    continue;
+                };
+
+                let key = Some((neighbour_coord, neighbour_index));
+                let crossing_cost = filter.cost(node_position, position, area, Some(link));
+                if !crossing_cost.is_finite() {
+                    continue;
+                }
+                match neighbours.iter_mut().find(|(other, ..)| *other == key) {
+                    Some((_, _, cost)) => *cost = cost.min(crossing_cost),
+                    None => neighbours.push((key, position, crossing_cost)),
+                }
+            }
+        }
+
+        for (key, position, edge_cost) in neighbours {
+            let neighbour_cost = cost + edge_cost;
+            let heuristic = position.distance(end_pos) * HEURISTIC_SCALE;
+            let total_cost = neighbour_cost + heuristic;
+
+            let neighbour_index = match lookup.entry(key) {
+                Entry::Occupied(entry) => {
+                    let neighbour = &mut nodes[*entry.get()];
+                    if neighbour.closed || total_cost >= neighbour.total_cost {
+                        continue;
+                    }
+
+                    neighbour.cost = neighbour_cost;
+                    neighbour.total_cost = total_cost;
+                    neighbour.parent = Some(node);
+
+                    *entry.get()
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(nodes.len());
+                    nodes.push(SearchNode {
+                        key,
+                        cost: neighbour_cost,
+                        total_cost,
+                        parent: Some(node),
+                        closed: false,
+                    });
+
+                    nodes.len() - 1
+                }
+            };
+
+            open_list.push(OpenListEntry {
+                total_cost,
+                node: neighbour_index,
+            });
+        }
+    }
+
+    // Walk back from the end, skipping the end itself.
+    let mut waypoints = Vec::new();
+    let mut current = nodes[end_node?].parent;
+    while let Some(node) = current {
+        let (tile_coord, node_index) = nodes[node].key?;
+        let (polygon, position) =
+            get_representatives(&mut representatives, nav_mesh, tile_coord, filter)[node_index]?;
+        waypoints.push((nav_mesh.poly_ref_unchecked(tile_coord, polygon), position));
+
+        current = nodes[node].parent;
+    }
+    waypoints.reverse();
+
+    Some(waypoints)
+}
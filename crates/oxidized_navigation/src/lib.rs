@@ -47,6 +47,10 @@ use std::marker::PhantomData;
 use std::num::{NonZeroU16, NonZeroU8};
 use std::sync::{Arc, RwLock};
 
+use agent::{
+    cancel_removed_path_requests_system, receive_paths_system, send_path_requests_system,
+    AgentPathTickets, PathRequestFailed,
+};
 use bevy::ecs::entity::EntityHashMap;
 use bevy::platform::collections::{HashMap, HashSet};
 use bevy::tasks::futures_lite::future;
@@ -69,19 +73,27 @@ use parry3d::shape::HeightField;
 use parry3d::{math::Isometry, na::Vector3, shape::TypedShape};
 use regions::build_regions;
 use smallvec::SmallVec;
+use tickets::{process_pathfinding_queue_system, PathfindingFinished, PathfindingQueue};
 use tiles::{create_nav_mesh_tile_from_poly_mesh, NavMeshTile, NavMeshTiles};
 
+pub mod agent;
 pub mod colliders;
 mod contour;
 pub mod conversion;
+pub mod corridor;
+pub mod crowd;
 #[cfg(feature = "debug_draw")]
 pub mod debug_draw;
 mod detail_mesh;
+pub mod flow_field;
 mod heightfields;
+pub mod hierarchy;
 mod math;
 mod mesher;
+pub mod path_following;
 pub mod query;
 mod regions;
+pub mod tickets;
 pub mod tiles;
 
 /// System sets containing the crate's systems.
@@ -92,6 +104,8 @@ pub enum OxidizedNavigation {
     RemovedComponent,
     /// Main systems, this creates the tile generation tasks & handles reacting to NavMeshAffector changes.
     Main,
+    /// Systems running the requests in [tickets::PathfindingQueue] & resolving [agent::PathRequest]s.
+    Pathfinding,
 }
 
 pub struct OxidizedNavigationPlugin<ColliderComponent> {
@@ -131,13 +145,16 @@ impl<C: OxidizedCollider> Plugin for OxidizedNavigationPlugin<C> {
             .init_resource::<NavMesh>()
             .init_resource::<GenerationTicker>()
             .init_resource::<NavMeshAffectorRelations>()
-            .init_resource::<ActiveGenerationTasks>();
+            .init_resource::<ActiveGenerationTasks>()
+            .init_resource::<PathfindingQueue>()
+            .init_resource::<AgentPathTickets>();
 
         app.configure_sets(
             self.schedule,
             (
                 OxidizedNavigation::RemovedComponent,
                 OxidizedNavigation::Main,
+                OxidizedNavigation::Pathfinding,
             )
                 .chain()
                 // Configure our systems to run before physics engines.
@@ -161,10 +178,25 @@ impl<C: OxidizedCollider> Plugin for OxidizedNavigationPlugin<C> {
                 .in_set(OxidizedNavigation::Main),
         );
 
+        app.add_systems(
+            self.schedule,
+            (
+                cancel_removed_path_requests_system,
+                send_path_requests_system,
+                process_pathfinding_queue_system,
+                receive_paths_system,
+            )
+                .chain()
+                .in_set(OxidizedNavigation::Pathfinding),
+        );
+
         app.register_type::<NavMeshAffector>()
-            .register_type::<NavMeshAreaType>();
+            .register_type::<NavMeshAreaType>()
+            .register_type::<NavMeshPolygonFlags>();
 
-        app.add_event::<TileGenerated>();
+        app.add_event::<TileGenerated>()
+            .add_event::<PathfindingFinished>()
+            .add_event::<PathRequestFailed>();
     }
 }
 
@@ -197,9 +229,17 @@ pub struct NavMeshAffector;
 #[reflect(Component)]
 pub struct NavMeshAreaType(pub Option<Area>);
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Reflect)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect)]
 pub struct Area(pub u16);
 
+/// Optional component to define the polygon flags of an entity. Defaults to ``0`` (no flags) when missing.
+///
+/// Flags are a bitmask of capabilities (for example "swim", "door" or "vehicle only") stored per polygon in [NavMeshTile::flags]. Unlike [Area] they don't affect cost but can be used by a [query::QueryFilter] to forbid traversal.
+/// Overlapping flags are combined.
+#[derive(Component, Reflect, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct NavMeshPolygonFlags(pub u16);
+
 /*
 *   Neighbours:
 *   0: (-1, 0),
@@ -482,6 +522,7 @@ type NavmeshAffectorChangedQueryFilter<C> = (
         Changed<GlobalTransform>,
         Changed<C>,
         Changed<NavMeshAffector>,
+        Changed<NavMeshPolygonFlags>,
     )>,
     With<NavMeshAffector>,
 );
@@ -613,6 +654,7 @@ fn send_tile_rebuild_tasks_system<C: OxidizedCollider>(
             &C::Component,
             &GlobalTransform,
             Option<&NavMeshAreaType>,
+            Option<&NavMeshPolygonFlags>,
         ),
         With<NavMeshAffector>,
     >,
@@ -660,10 +702,11 @@ fn send_tile_rebuild_tasks_system<C: OxidizedCollider>(
         let mut heightfield_collections = Vec::new();
 
         let mut collider_iter = collider_query.iter_many(affectors.iter());
-        while let Some((entity, collider, global_transform, nav_mesh_affector)) =
+        while let Some((entity, collider, global_transform, nav_mesh_affector, polygon_flags)) =
             collider_iter.fetch_next()
         {
             let area = nav_mesh_affector.map_or(Some(Area(0)), |area_type| area_type.0);
+            let flags = polygon_flags.map_or(0, |flags| flags.0);
 
             let geometry_result = get_geometry_type(C::oxidized_into_typed_shape(collider));
             let transform = global_transform.compute_transform();
@@ -672,6 +715,7 @@ fn send_tile_rebuild_tasks_system<C: OxidizedCollider>(
                 entity,
                 transform,
                 area,
+                flags,
                 &mut geometry_collections,
                 &mut heightfield_collections,
                 &mut heightfields,
@@ -696,11 +740,13 @@ fn send_tile_rebuild_tasks_system<C: OxidizedCollider>(
     heightfields.clear();
 }
 
+#[expect(clippy::too_many_arguments)]
 fn handle_geometry_result(
     type_to_convert: GeometryResult,
     entity: Entity,
     global_transform: Transform,
     area: Option<Area>,
+    flags: u16,
     geometry_collections: &mut Vec<GeometryCollection>,
     heightfield_collections: &mut Vec<Arc<HeightFieldCollection>>,
     heightfields: &mut EntityHashMap<Arc<HeightFieldCollection>>,
@@ -711,6 +757,7 @@ fn handle_geometry_result(
                 transform: global_transform,
                 geometry_to_convert,
                 area,
+                flags,
             });
         }
         GeometryResult::Heightfield(heightfield) => {
@@ -722,6 +769,7 @@ fn handle_geometry_result(
                     transform: global_transform,
                     heightfield: heightfield.clone(),
                     area,
+                    flags,
                 });
 
                 heightfields.insert(entity, heightfield.clone());
@@ -743,6 +791,7 @@ fn handle_geometry_result(
                     entity,
                     transform,
                     area,
+                    flags,
                     geometry_collections,
                     heightfield_collections,
                     heightfields,
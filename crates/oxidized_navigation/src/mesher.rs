@@ -14,6 +14,7 @@ pub struct PolyMesh {
     pub polygons: Vec<[u32; VERTICES_IN_TRIANGLE]>, //
     pub edges: Vec<[EdgeConnection; VERTICES_IN_TRIANGLE]>, // For each polygon edge points to a polygon (if any) that shares the edge.
     pub areas: Vec<Area>,
+    pub flags: Vec<u16>,
     pub regions: Vec<u16>,
 }
 
@@ -44,6 +45,7 @@ pub fn build_poly_mesh(
         polygons: Vec::with_capacity(max_tris),
         edges: Vec::with_capacity(max_tris),
         areas: Vec::with_capacity(max_tris),
+        flags: Vec::with_capacity(max_tris),
         regions: Vec::with_capacity(max_tris),
     };
 
@@ -89,6 +91,7 @@ pub fn build_poly_mesh(
                 ]);
 
                 poly_mesh.areas.push(contour.area);
+                poly_mesh.flags.push(contour.flags);
                 poly_mesh.regions.push(contour.region);
             }
         }
@@ -152,6 +155,15 @@ impl EdgeConnectionDirection {
             EdgeConnectionDirection::ZNegative => coordinate - UVec2::Y,
         }
     }
+
+    pub fn opposite(&self) -> Self {
+        match self {
+            EdgeConnectionDirection::XNegative => EdgeConnectionDirection::XPositive,
+            EdgeConnectionDirection::ZPositive => EdgeConnectionDirection::ZNegative,
+            EdgeConnectionDirection::XPositive => EdgeConnectionDirection::XNegative,
+            EdgeConnectionDirection::ZNegative => EdgeConnectionDirection::ZPositive,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
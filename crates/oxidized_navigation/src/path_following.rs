@@ -0,0 +1,268 @@
+//! Module for moving entities along their [NavPath].
+//!
+//! Add [PathFollowingPlugin] & a [PathFollower] to any entity with a [Transform] to have it follow the [NavPath]s found for its [crate::agent::PathRequest]s.
+use bevy::prelude::*;
+
+use crate::{
+    agent::{NavPath, PathRequestFailed},
+    query::{FindPathError, PathStatus},
+    NavMesh, NavMeshSettings,
+};
+
+/// Moves entities with a [PathFollower] & [NavPath] along the path. Requires [crate::OxidizedNavigationPlugin].
+///
+/// Runs in [Update]. Sends [PathCompleted] & [PathFailed] events.
+pub struct PathFollowingPlugin;
+impl Plugin for PathFollowingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PathCompleted>().add_event::<PathFailed>();
+
+        app.add_systems(
+            Update,
+            (forward_failed_path_requests_system, follow_path_system).in_set(PathFollowingSystems),
+        );
+    }
+}
+
+/// System set containing the systems of [PathFollowingPlugin].
+#[derive(SystemSet, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct PathFollowingSystems;
+
+/// Movement settings & state for following a [NavPath].
+///
+/// Positions are expected to be at the entity's feet, the entity's height is snapped to the nav-mesh surface while moving.
+#[derive(Component, Debug, Clone)]
+pub struct PathFollower {
+    /// Maximum speed in world units per second.
+    pub max_speed: f32,
+    /// Acceleration & deceleration in world units per second squared.
+    pub acceleration: f32,
+    /// Distance at which a corner counts as reached & the follower turns toward the next one.
+    pub arrival_radius: f32,
+    /// Distance from the end of the path at which the follower stops & the path is completed.
+    pub stopping_distance: f32,
+    /// Maximum distance above & below the follower to search for the nav-mesh surface when snapping its height. See [crate::tiles::NavMeshTiles::get_height_at].
+    pub height_search: f32,
+    velocity: Vec3,
+    /// Index of the corner currently moved toward.
+    corner: usize,
+}
+
+impl Default for PathFollower {
+    fn default() -> Self {
+        Self {
+            max_speed: 5.0,
+            acceleration: 20.0,
+            arrival_radius: 0.25,
+            stopping_distance: 0.1,
+            height_search: 1.0,
+            velocity: Vec3::ZERO,
+            corner: 0,
+        }
+    }
+}
+
+impl PathFollower {
+    /// Setter for [PathFollower::max_speed]
+    pub fn with_max_speed(mut self, max_speed: f32) -> Self {
+        self.max_speed = max_speed;
+        self
+    }
+
+    /// Setter for [PathFollower::acceleration]
+    pub fn with_acceleration(mut self, acceleration: f32) -> Self {
+        self.acceleration = acceleration;
+        self
+    }
+
+    /// Setter for [PathFollower::arrival_radius]
+    pub fn with_arrival_radius(mut self, arrival_radius: f32) -> Self {
+        self.arrival_radius = arrival_radius;
+        self
+    }
+
+    /// Setter for [PathFollower::stopping_distance]
+    pub fn with_stopping_distance(mut self, stopping_distance: f32) -> Self {
+        self.stopping_distance = stopping_distance;
+        self
+    }
+
+    /// Setter for [PathFollower::height_search]
+    pub fn with_height_search(mut self, height_search: f32) -> Self {
+        self.height_search = height_search;
+        self
+    }
+
+    /// Returns the current velocity of the follower. Always horizontal.
+    #[inline]
+    pub fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+
+    /// Returns the index of the [NavPath] corner the follower is moving toward.
+    #[inline]
+    pub fn corner(&self) -> usize {
+        self.corner
+    }
+}
+
+/// Event sent when a [PathFollower] has reached the end of a complete [NavPath]. The [NavPath] is removed.
+#[derive(Event, Debug, Clone)]
+pub struct PathCompleted {
+    pub entity: Entity,
+}
+
+/// Why a [PathFollower] couldn't reach its target.
+#[derive(Debug, Clone)]
+pub enum PathFailedReason {
+    /// The follower reached the end of a path that doesn't reach the target. See [PathStatus].
+    TargetUnreachable(PathStatus),
+    /// No path could be found for the entity's [crate::agent::PathRequest].
+    RequestFailed(FindPathError),
+}
+
+/// Event sent when a [PathFollower] couldn't reach its target. Any [NavPath] is removed.
+#[derive(Event, Debug, Clone)]
+pub struct PathFailed {
+    pub entity: Entity,
+    pub reason: PathFailedReason,
+}
+
+fn forward_failed_path_requests_system(
+    mut failed_requests: EventReader<PathRequestFailed>,
+    mut failed_events: EventWriter<PathFailed>,
+    follower_query: Query<(), With<PathFollower>>,
+) {
+    for failed in failed_requests.read() {
+        if follower_query.contains(failed.entity) {
+            failed_events.write(PathFailed {
+                entity: failed.entity,
+                reason: PathFailedReason::RequestFailed(failed.error.clone()),
+            });
+        }
+    }
+}
+
+fn follow_path_system(
+    mut commands: Commands,
+    mut follower_query: Query<(Entity, &mut Transform, &mut PathFollower, Ref<NavPath>)>,
+    mut completed_events: EventWriter<PathCompleted>,
+    mut failed_events: EventWriter<PathFailed>,
+    time: Res<Time>,
+    nav_mesh_settings: Res<NavMeshSettings>,
+    nav_mesh: Res<NavMesh>,
+) {
+    let delta_seconds = time.delta_secs();
+    let nav_mesh = nav_mesh.get();
+    // Don't block on tile generation, skip snapping for a frame instead.
+    let nav_mesh = nav_mesh.try_read().ok();
+
+    for (entity, mut transform, mut follower, nav_path) in follower_query.iter_mut() {
+        if nav_path.is_changed() {
+            // The first corner is where the path started.
+            follower.corner = 1;
+        }
+
+        let corners = &nav_path.corners;
+        let mut position = transform.translation;
+
+        let reached_end = follow_corners(&mut follower, corners, &mut position, delta_seconds);
+
+        if let Some((_, height)) = nav_mesh.as_ref().and_then(|nav_mesh| {
+            nav_mesh.get_height_at(&nav_mesh_settings, position, follower.height_search)
+        }) {
+            position.y = height;
+        }
+        transform.translation = position;
+
+        if !reached_end {
+            continue;
+        }
+
+        follower.velocity = Vec3::ZERO;
+        commands.entity(entity).remove::<NavPath>();
+
+        if nav_path.status.is_complete() {
+            completed_events.write(PathCompleted { entity });
+        } else {
+            failed_events.write(PathFailed {
+                entity,
+                reason: PathFailedReason::TargetUnreachable(nav_path.status),
+            });
+        }
+    }
+}
+
+/// Moves ``position`` toward the follower's current corner. Returns true if the end of the path has been reached.
+fn follow_corners(
+    follower: &mut PathFollower,
+    corners: &[Vec3],
+    position: &mut Vec3,
+    delta_seconds: f32,
+) -> bool {
+    let Some(end) = corners.last() else {
+        return true;
+    };
+    if follower.corner >= corners.len() {
+        return true;
+    }
+
+    // Skip corners that have already been reached.
+    while follower.corner < corners.len() - 1
+        && position.xz().distance(corners[follower.corner].xz()) <= follower.arrival_radius
+    {
+        follower.corner += 1;
+    }
+
+    let remaining_distance = position.xz().distance(corners[follower.corner].xz())
+        + corners[follower.corner..]
+            .windows(2)
+            .map(|pair| pair[0].xz().distance(pair[1].xz()))
+            .sum::<f32>();
+    if remaining_distance <= follower.stopping_distance {
+        return true;
+    }
+
+    // Slow down so we come to a stop at the stopping distance instead of overshooting the end.
+    let braking_distance = (remaining_distance - follower.stopping_distance).max(0.0);
+    let desired_speed = follower
+        .max_speed
+        .min((2.0 * follower.acceleration * braking_distance).sqrt());
+
+    let corner = corners[follower.corner];
+    let to_corner = Vec3::new(corner.x - position.x, 0.0, corner.z - position.z);
+    let desired_velocity = to_corner.normalize_or_zero() * desired_speed;
+
+    follower.velocity = follower
+        .velocity
+        .move_towards(desired_velocity, follower.acceleration * delta_seconds);
+
+    // Walk the step along the corners, carrying whatever is left after reaching a corner over to the next one.
+    let speed = follower.velocity.length();
+    let mut step_length = speed * delta_seconds;
+    loop {
+        let corner = corners[follower.corner];
+        let to_corner = Vec3::new(corner.x - position.x, 0.0, corner.z - position.z);
+        let distance_to_corner = to_corner.length();
+
+        if step_length < distance_to_corner {
+            *position += to_corner / distance_to_corner * step_length;
+            return false;
+        }
+
+        position.x = corner.x;
+        position.z = corner.z;
+        step_length -= distance_to_corner;
+
+        if follower.corner == corners.len() - 1 {
+            return position.xz().distance(end.xz()) <= follower.stopping_distance;
+        }
+        follower.corner += 1;
+
+        // Turn toward the next corner without losing speed.
+        let next_corner = corners[follower.corner];
+        let to_next_corner = Vec3::new(next_corner.x - position.x, 0.0, next_corner.z - position.z);
+        follower.velocity =
+            to_next_corner.normalize_or(follower.velocity.normalize_or_zero()) * speed;
+    }
+}
@@ -1,13 +1,28 @@
 //! Module for querying the nav-mesh.
 
-use bevy::prelude::{UVec2, Vec3};
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, VecDeque},
+    num::NonZeroUsize,
+};
+
+use bevy::{
+    math::Vec3Swizzles,
+    platform::collections::{hash_map::Entry, HashMap, HashSet},
+    prelude::{UVec2, Vec3},
+};
 
 use crate::{
-    tiles::{Link, NavMeshTiles},
-    NavMeshSettings,
+    hierarchy::find_abstract_path,
+    mesher::VERTICES_IN_TRIANGLE,
+    tiles::{distance_point_to_segment_2d, in_polygon, Link, NavMeshTiles, PolyRef},
+    Area, NavMeshSettings,
 };
 
-const HEURISTIC_SCALE: f32 = 0.999;
+pub(crate) const HEURISTIC_SCALE: f32 = 0.999;
+
+/// Half extents of the box searched for start & end polygons when no extents are supplied. See [NavMeshTiles::project_point].
+pub const DEFAULT_POSITION_SEARCH_EXTENTS: Vec3 = Vec3::splat(5.0);
 
 #[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
 enum NodeState {
@@ -28,8 +43,212 @@ struct NavMeshNode {
     parent: Option<usize>,
 }
 
+/// Filter deciding which polygons a query may traverse & how much it costs to do so.
+///
+/// Implement this to customize pathfinding, for example to forbid certain areas for some agents. See [DefaultQueryFilter] for a filter based on area types & polygon flags.
+pub trait QueryFilter {
+    /// Returns true if the polygon may be traversed.
+    ///
+    /// * ``tile`` - Tile coordinate of the polygon.
+    /// * ``polygon`` - Index of the polygon within the tile.
+    /// * ``area`` - Area type of the polygon.
+    /// * ``flags`` - Flags of the polygon. See [crate::NavMeshPolygonFlags].
+    fn pass_filter(&self, tile: UVec2, polygon: u16, area: Area, flags: u16) -> bool;
+
+    /// Returns the cost of moving from ``from`` to ``to`` across a polygon.
+    ///
+    /// * ``area`` - Area type of the polygon being moved across.
+    /// * ``link`` - The link being crossed into the next polygon. **``None``** for the final segment to the destination.
+    ///
+    /// Returning a non-finite cost, like [f32::INFINITY], forbids the move.
+    fn cost(&self, from: Vec3, to: Vec3, area: Area, link: Option<&Link>) -> f32;
+}
+
+/// The default [QueryFilter]. Filters polygons by their area type & flags, multiplying the distance travelled across an area by a per-area cost.
+#[derive(Debug, Clone, Default)]
+pub struct DefaultQueryFilter {
+    /// Areas that may be traversed. If **``None``** all areas not in ``excluded_areas`` may be traversed.
+    pub included_areas: Option<HashSet<Area>>,
+    /// Areas that may never be traversed.
+    pub excluded_areas: HashSet<Area>,
+    /// Multipliers for area cost, use to prioritize or deprioritize taking certain paths. Areas not present default to 1.0. Lesser value means the path costs less.
+    pub area_costs: HashMap<Area, f32>,
+    /// Polygons must have at least one of these flags to be traversed. If **``None``** polygons may have any flags (including none).
+    pub included_flags: Option<u16>,
+    /// Polygons with any of these flags may never be traversed.
+    pub excluded_flags: u16,
+    /// Extra cost for moving uphill & downhill. If **``None``** only the distance travelled is considered.
+    pub slope_costs: Option<SlopeCosts>,
+}
+impl DefaultQueryFilter {
+    /// Setter for the cost multiplier of ``area``.
+    pub fn with_area_cost(mut self, area: Area, cost: f32) -> Self {
+        self.area_costs.insert(area, cost);
+
+        self
+    }
+
+    /// Only allow traversing the supplied areas.
+    pub fn with_included_areas(mut self, areas: impl IntoIterator<Item = Area>) -> Self {
+        self.included_areas = Some(areas.into_iter().collect());
+
+        self
+    }
+
+    /// Never allow traversing ``area``.
+    pub fn with_excluded_area(mut self, area: Area) -> Self {
+        self.excluded_areas.insert(area);
+
+        self
+    }
+
+    /// Only allow traversing polygons with at least one of ``flags``.
+    pub fn with_included_flags(mut self, flags: u16) -> Self {
+        self.included_flags = Some(flags);
+
+        self
+    }
+
+    /// Never allow traversing polygons with any of ``flags``.
+    pub fn with_excluded_flags(mut self, flags: u16) -> Self {
+        self.excluded_flags |= flags;
+
+        self
+    }
+
+    /// Setter for [DefaultQueryFilter::slope_costs]
+    pub fn with_slope_costs(mut self, slope_costs: SlopeCosts) -> Self {
+        self.slope_costs = Some(slope_costs);
+
+        self
+    }
+
+    /// Returns the cost multiplier of ``area``.
+    #[inline]
+    pub fn get_area_cost(&self, area: Area) -> f32 {
+        *self.area_costs.get(&area).unwrap_or(&1.0)
+    }
+}
+impl QueryFilter for DefaultQueryFilter {
+    fn pass_filter(&self, _tile: UVec2, _polygon: u16, area: Area, flags: u16) -> bool {
+        self.included_areas
+            .as_ref()
+            .is_none_or(|included_areas| included_areas.contains(&area))
+            && !self.excluded_areas.contains(&area)
+            && self
+                .included_flags
+                .is_none_or(|included_flags| flags & included_flags != 0)
+            && flags & self.excluded_flags == 0
+    }
+
+    fn cost(&self, from: Vec3, to: Vec3, area: Area, _link: Option<&Link>) -> f32 {
+        let slope_multiplier = self
+            .slope_costs
+            .map_or(1.0, |slope_costs| slope_costs.get_multiplier(from, to));
+
+        from.distance(to) * self.get_area_cost(area) * slope_multiplier
+    }
+}
+
+/// Costs for changing height along a path, see [DefaultQueryFilter::slope_costs].
+///
+/// The slope of a move is the angle between it & the XZ-plane. Paths are searched between points on polygon edges, so slopes follow the heights of the nav-mesh vertices.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SlopeCosts {
+    /// Extra cost multiplier per radian of slope when moving uphill. ``0.0`` adds no extra cost.
+    pub uphill_cost: f32,
+    /// Extra cost multiplier per radian of slope when moving downhill. ``0.0`` adds no extra cost.
+    pub downhill_cost: f32,
+    /// Steepest slope in radians that may be climbed. If **``None``** any slope may be climbed.
+    pub max_uphill_slope: Option<f32>,
+    /// Steepest slope in radians that may be descended. If **``None``** any slope may be descended.
+    pub max_downhill_slope: Option<f32>,
+}
+impl SlopeCosts {
+    /// Setter for [SlopeCosts::uphill_cost]
+    pub fn with_uphill_cost(mut self, uphill_cost: f32) -> Self {
+        self.uphill_cost = uphill_cost;
+
+        self
+    }
+
+    /// Setter for [SlopeCosts::downhill_cost]
+    pub fn with_downhill_cost(mut self, downhill_cost: f32) -> Self {
+        self.downhill_cost = downhill_cost;
+
+        self
+    }
+
+    /// Setter for [SlopeCosts::max_uphill_slope]
+    pub fn with_max_uphill_slope(mut self, max_uphill_slope: f32) -> Self {
+        self.max_uphill_slope = Some(max_uphill_slope);
+
+        self
+    }
+
+    /// Setter for [SlopeCosts::max_downhill_slope]
+    pub fn with_max_downhill_slope(mut self, max_downhill_slope: f32) -> Self {
+        self.max_downhill_slope = Some(max_downhill_slope);
+
+        self
+    }
+
+    /// Returns the multiplier for the distance of moving from ``from`` to ``to``. [f32::INFINITY] if the move is steeper than allowed.
+    pub fn get_multiplier(&self, from: Vec3, to: Vec3) -> f32 {
+        let rise = to.y - from.y;
+        let slope = rise.abs().atan2(from.xz().distance(to.xz()));
+
+        let (cost, max_slope) = if rise >= 0.0 {
+            (self.uphill_cost, self.max_uphill_slope)
+        } else {
+            (self.downhill_cost, self.max_downhill_slope)
+        };
+
+        if max_slope.is_some_and(|max_slope| slope > max_slope) {
+            return f32::INFINITY;
+        }
+
+        1.0 + slope * cost
+    }
+}
+
+/// Filter used when no filter is supplied. Lets everything through at the cost of the distance travelled.
+pub(crate) struct PassAllFilter;
+impl QueryFilter for PassAllFilter {
+    fn pass_filter(&self, _tile: UVec2, _polygon: u16, _area: Area, _flags: u16) -> bool {
+        true
+    }
+
+    fn cost(&self, from: Vec3, to: Vec3, _area: Area, _link: Option<&Link>) -> f32 {
+        from.distance(to)
+    }
+}
+
+/// Entry in the A* open list. Ordered so that [BinaryHeap] pops the entry with the lowest total cost first.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct OpenListEntry {
+    pub(crate) total_cost: f32,
+    pub(crate) node: usize,
+}
+impl PartialEq for OpenListEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for OpenListEntry {}
+impl PartialOrd for OpenListEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for OpenListEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.total_cost.total_cmp(&self.total_cost)
+    }
+}
+
 /// Errors returned by [find_polygon_path]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum FindPolygonPathError {
     /// Nav-mesh couldn't be retrieved from lock.
     NavMeshUnavailable,
@@ -39,45 +258,507 @@ pub enum FindPolygonPathError {
     NoValidEndPolygon,
 }
 
-/// Performs A* pathfinding on the supplied nav-mesh.
-/// Returning the polygons crossed as a [Vec] containing the tile coordinate ([UVec2]) & polygon index ([u16]) or [FindPathError]
+/// Describes how far a path got toward its destination.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathStatus {
+    /// The path reaches the destination.
+    Complete,
+    /// The destination isn't reachable. The path leads to the closest reachable point instead.
+    Partial {
+        /// The point on the nav-mesh closest to the destination that the path reaches.
+        closest_point: Vec3,
+    },
+    /// The search ran out of nodes before reaching the destination. The path leads to the closest point found before running out.
+    ///
+    /// See [NavMeshQuery::new].
+    OutOfNodes {
+        /// The point on the nav-mesh closest to the destination that the path reaches.
+        closest_point: Vec3,
+    },
+}
+impl PathStatus {
+    /// Returns true if the path reaches the destination.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        matches!(self, PathStatus::Complete)
+    }
+}
+
+/// Path returned by [find_polygon_path_hierarchical].
 ///
-/// * ``nav_mesh`` - Nav-mesh to pathfind across.
-/// * ``nav_mesh_settings`` - Nav-mesh settings used to generate ``nav_mesh``.
-/// * ``start_pos`` - Starting position for the path.
-/// * ``end_pos`` - Destination position for the path, i.e where you want to go.
-/// * ``position_search_radius`` - Radius to search for a start & end polygon in. In world units. If **``None``** is supplied a default value of ``5.0`` is used.
-/// * ``area_cost_multipliers`` - Multipliers for area cost, use to prioritize or deprioritize taking certain paths. Values not present default to 1.0. Lesser value means the path costs less.
-pub fn find_polygon_path(
-    nav_mesh: &NavMeshTiles,
-    nav_mesh_settings: &NavMeshSettings,
-    start_pos: Vec3,
+/// Only the part up to the first tile border crossing is refined into polygons. Refine the rest as it's needed with [NavMeshQuery::refine_hierarchical_path].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HierarchicalPath {
+    /// The polygons crossed so far, in order from the start.
+    pub polygons: Vec<PolyRef>,
+    /// Whether the path reaches the destination. Only known for sure once the path is fully refined.
+    pub status: PathStatus,
+    /// Waypoint the refined polygons lead to as (polygon, position).
+    current: (PolyRef, Vec3),
+    /// Waypoints left to refine as (polygon, position), ending with the destination.
+    pending: VecDeque<(PolyRef, Vec3)>,
+}
+impl HierarchicalPath {
+    /// Returns true if every segment has been refined into polygons.
+    #[inline]
+    pub fn is_refined(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Returns the abstract waypoints left to refine as (representative polygon, position), ending with the destination.
+    pub fn waypoints(&self) -> impl Iterator<Item = &(PolyRef, Vec3)> {
+        self.pending.iter()
+    }
+}
+
+/// Polygon path returned by [find_polygon_path].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolygonPath {
+    /// The polygons crossed, in order from start to end.
+    pub polygons: Vec<PolyRef>,
+    /// Whether the path reaches the destination.
+    pub status: PathStatus,
+}
+
+/// World space path returned by [find_path].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    /// The string pulled path, in order from start to end.
+    pub points: Vec<Vec3>,
+    /// Whether the path reaches the destination.
+    pub status: PathStatus,
+}
+
+/// Reusable state for running pathfinding queries.
+///
+/// Owns the node pool, open list & scratch buffers used by A* so that they can be reused between queries instead of being allocated for every call.
+/// Keep one of these around per thread (or per agent) if you are running a lot of queries.
+///
+/// The free functions [find_polygon_path] & [find_path] create a temporary [NavMeshQuery] with no node limit.
+#[derive(Debug, Default)]
+pub struct NavMeshQuery {
+    max_nodes: Option<NonZeroUsize>,
+    nodes: Vec<NavMeshNode>,
+    node_lookup: HashMap<(UVec2, u16), usize>,
+    open_list: BinaryHeap<OpenListEntry>,
+    polygon_path: Vec<PolyRef>,
+    /// State of the sliced search in progress, see [NavMeshQuery::init_sliced_find_path].
+    search: Option<SearchState>,
+    /// Generation of each tile touched by the current search when it was first touched. Used to detect changes between slices.
+    tile_generations: HashMap<UVec2, u64>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum SearchStatus {
+    InProgress,
+    Finished,
+    Failed,
+}
+
+#[derive(Debug)]
+struct SearchState {
+    end_tile: UVec2,
+    end_polygon: u16,
     end_pos: Vec3,
-    position_search_radius: Option<f32>,
-    area_cost_multipliers: Option<&[f32]>, // TODO: A slice might not be the best choice when there are many area types.
-) -> Result<Vec<(UVec2, u16)>, FindPolygonPathError> {
-    let search_radius = position_search_radius.unwrap_or(5.0);
+    last_best_node: usize,
+    last_best_node_cost: f32,
+    /// Whether the search had to skip any polygons because the node pool was exhausted.
+    out_of_nodes: bool,
+    status: SearchStatus,
+}
 
-    let Some((start_tile, start_poly, start_pos)) =
-        nav_mesh.find_closest_polygon_in_box(nav_mesh_settings, start_pos, search_radius)
-    else {
-        return Err(FindPolygonPathError::NoValidStartPolygon);
-    };
+/// Status returned by [NavMeshQuery::update_sliced_find_path].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SlicedFindPathStatus {
+    /// The search hasn't finished yet. Call [NavMeshQuery::update_sliced_find_path] again.
+    InProgress,
+    /// The search has finished. Call [NavMeshQuery::finalize_sliced_find_path] to retrieve the path.
+    Finished,
+}
 
-    let Some((end_tile, end_poly, end_pos)) =
-        nav_mesh.find_closest_polygon_in_box(nav_mesh_settings, end_pos, search_radius)
-    else {
-        return Err(FindPolygonPathError::NoValidEndPolygon);
-    };
+/// Errors returned by [NavMeshQuery::update_sliced_find_path] & [NavMeshQuery::finalize_sliced_find_path]
+#[derive(Debug)]
+pub enum SlicedFindPathError {
+    /// No sliced search has been started with [NavMeshQuery::init_sliced_find_path].
+    NotInitialized,
+    /// A tile the search has visited was changed or removed since the search started. The search has to be restarted.
+    NavMeshChanged,
+}
+
+impl NavMeshQuery {
+    /// Creates a new query object.
+    ///
+    /// * ``max_nodes`` - Maximum number of polygons a single search may visit. A value of ``None`` will result in no limit.
+    pub fn new(max_nodes: Option<NonZeroUsize>) -> Self {
+        Self {
+            max_nodes,
+            ..Default::default()
+        }
+    }
 
-    if start_tile == end_tile && start_poly == end_poly {
-        return Ok(vec![(start_tile, start_poly)]);
+    /// Returns the maximum number of nodes a single search may visit.
+    pub fn max_nodes(&self) -> Option<NonZeroUsize> {
+        self.max_nodes
     }
 
-    let mut nodes = Vec::with_capacity(10);
-    let mut open_list = Vec::with_capacity(5);
+    /// Setter for the maximum number of nodes a single search may visit. A value of ``None`` will result in no limit.
+    pub fn set_max_nodes(&mut self, max_nodes: Option<NonZeroUsize>) {
+        self.max_nodes = max_nodes;
+    }
+
+    /// Performs A* pathfinding on the supplied nav-mesh.
+    /// Returning the polygons crossed as a [PolygonPath] or [FindPolygonPathError]
+    ///
+    /// See [find_polygon_path] for a description of the parameters.
+    pub fn find_polygon_path(
+        &mut self,
+        nav_mesh: &NavMeshTiles,
+        nav_mesh_settings: &NavMeshSettings,
+        start_pos: Vec3,
+        end_pos: Vec3,
+        position_search_extents: Option<Vec3>,
+        filter: Option<&dyn QueryFilter>,
+    ) -> Result<PolygonPath, FindPolygonPathError> {
+        let mut polygons = Vec::new();
+
+        let status = self.find_polygon_path_into(
+            nav_mesh,
+            nav_mesh_settings,
+            start_pos,
+            end_pos,
+            position_search_extents,
+            filter,
+            &mut polygons,
+        )?;
+
+        Ok(PolygonPath { polygons, status })
+    }
+
+    /// Performs hierarchical pathfinding on the supplied nav-mesh.
+    /// Returning a [HierarchicalPath] refined up to the first tile border crossing or [FindPolygonPathError]
+    ///
+    /// See [find_polygon_path_hierarchical] for a description of the parameters.
+    pub fn find_polygon_path_hierarchical(
+        &mut self,
+        nav_mesh: &NavMeshTiles,
+        nav_mesh_settings: &NavMeshSettings,
+        start_pos: Vec3,
+        end_pos: Vec3,
+        position_search_extents: Option<Vec3>,
+        filter: Option<&dyn QueryFilter>,
+    ) -> Result<HierarchicalPath, FindPolygonPathError> {
+        let (start, end) = find_search_polygons(
+            nav_mesh,
+            nav_mesh_settings,
+            start_pos,
+            end_pos,
+            position_search_extents,
+            filter,
+        )?;
+
+        // Unreachable destinations are left to the regular search, which finds the closest reachable point.
+        let waypoints = if nav_mesh.are_connected(start.0, end.0) {
+            find_abstract_path(nav_mesh, start, end, filter)
+        } else {
+            None
+        };
+
+        let mut path = HierarchicalPath {
+            polygons: Vec::new(),
+            status: PathStatus::Complete,
+            current: start,
+            pending: VecDeque::new(),
+        };
+        match waypoints {
+            Some(waypoints) => {
+                path.pending.extend(waypoints);
+                path.pending.push_back(end);
+                self.refine_hierarchical_path(nav_mesh, &mut path, 1, filter);
+            }
+            None => {
+                // Both positions are in the same tile or no abstract path exists, search regularly.
+                let mut search = self.init_search_from_polygons(nav_mesh, start, end);
+                self.step_search(&mut search, nav_mesh, filter, usize::MAX);
+                path.status = self.finish_search(nav_mesh, search, &mut path.polygons);
+            }
+        }
+
+        Ok(path)
+    }
+
+    /// Refines up to ``max_segments`` more segments of ``path``, each running from one abstract waypoint to the next, appending their polygons to [HierarchicalPath::polygons].
+    ///
+    /// Returns true once ``path`` is fully refined. If a segment can't be refined, e.g. because the nav-mesh has changed, the rest of the path is found with a regular search instead.
+    ///
+    /// * ``filter`` - Should be the filter the path was found with. See [find_polygon_path].
+    pub fn refine_hierarchical_path(
+        &mut self,
+        nav_mesh: &NavMeshTiles,
+        path: &mut HierarchicalPath,
+        max_segments: usize,
+        filter: Option<&dyn QueryFilter>,
+    ) -> bool {
+        let mut segment = Vec::new();
+        for _ in 0..max_segments {
+            let Some(mut next) = path.pending.pop_front() else {
+                break;
+            };
+
+            let mut search = self.init_search_from_polygons(nav_mesh, path.current, next);
+            self.step_search(&mut search, nav_mesh, filter, usize::MAX);
+            segment.clear();
+            if !self
+                .finish_search(nav_mesh, search, &mut segment)
+                .is_complete()
+            {
+                next = path.pending.pop_back().unwrap_or(next);
+                path.pending.clear();
+
+                let mut search = self.init_search_from_polygons(nav_mesh, path.current, next);
+                self.step_search(&mut search, nav_mesh, filter, usize::MAX);
+                segment.clear();
+                path.status = self.finish_search(nav_mesh, search, &mut segment);
+            }
+
+            for polygon in segment.iter() {
+                // Cut out loops where a segment doubles back over the previous one.
+                if let Some(index) = path.polygons.iter().rposition(|other| other == polygon) {
+                    path.polygons.truncate(index + 1);
+                    continue;
+                }
+
+                path.polygons.push(*polygon);
+            }
+            path.current = next;
+        }
+
+        path.is_refined()
+    }
+
+    /// Performs A* pathfinding and string pulling on the supplied nav-mesh.
+    /// Returns the path as a [Path] or [FindPathError]
+    ///
+    /// See [find_path] for a description of the parameters.
+    pub fn find_path(
+        &mut self,
+        nav_mesh: &NavMeshTiles,
+        nav_mesh_settings: &NavMeshSettings,
+        start_pos: Vec3,
+        end_pos: Vec3,
+        position_search_extents: Option<Vec3>,
+        filter: Option<&dyn QueryFilter>,
+    ) -> Result<Path, FindPathError> {
+        let mut polygon_path = std::mem::take(&mut self.polygon_path);
+
+        let result = self
+            .find_polygon_path_into(
+                nav_mesh,
+                nav_mesh_settings,
+                start_pos,
+                end_pos,
+                position_search_extents,
+                filter,
+                &mut polygon_path,
+            )
+            .map_err(FindPathError::PolygonPath)
+            .and_then(|status| {
+                let points =
+                    perform_string_pulling_on_path(nav_mesh, start_pos, end_pos, &polygon_path)
+                        .map_err(FindPathError::StringPulling)?;
+
+                Ok(Path { points, status })
+            });
+
+        self.polygon_path = polygon_path;
+
+        result
+    }
+
+    /// Starts a sliced (time-sliced) pathfinding search.
+    ///
+    /// Sliced searches allow spreading a long search over several frames. Call [NavMeshQuery::update_sliced_find_path] until it returns [SlicedFindPathStatus::Finished] & then [NavMeshQuery::finalize_sliced_find_path] to get the path.
+    ///
+    /// The nav-mesh lock doesn't need to be held between calls, allowing tile generation to continue between slices. Should any tile the search has visited change the search will fail with [SlicedFindPathError::NavMeshChanged].
+    ///
+    /// See [find_polygon_path] for a description of the parameters.
+    pub fn init_sliced_find_path(
+        &mut self,
+        nav_mesh: &NavMeshTiles,
+        nav_mesh_settings: &NavMeshSettings,
+        start_pos: Vec3,
+        end_pos: Vec3,
+        position_search_extents: Option<Vec3>,
+        filter: Option<&dyn QueryFilter>,
+    ) -> Result<(), FindPolygonPathError> {
+        let search = self.init_search(
+            nav_mesh,
+            nav_mesh_settings,
+            start_pos,
+            end_pos,
+            position_search_extents,
+            filter,
+        )?;
+        self.search = Some(search);
+
+        Ok(())
+    }
+
+    /// Runs at most ``max_iterations`` iterations of the search started by [NavMeshQuery::init_sliced_find_path].
+    ///
+    /// * ``nav_mesh`` - Nav-mesh to pathfind across. Must be the same nav-mesh the search was started on.
+    /// * ``max_iterations`` - Maximum amount of polygons to expand during this update.
+    /// * ``filter`` - Filter deciding which polygons may be traversed & at what cost. Should be the same for every update of a search. See [find_polygon_path].
+    pub fn update_sliced_find_path(
+        &mut self,
+        nav_mesh: &NavMeshTiles,
+        max_iterations: usize,
+        filter: Option<&dyn QueryFilter>,
+    ) -> Result<SlicedFindPathStatus, SlicedFindPathError> {
+        let Some(mut search) = self.search.take() else {
+            return Err(SlicedFindPathError::NotInitialized);
+        };
+
+        if search.status == SearchStatus::InProgress
+            && visited_tiles_changed(&self.tile_generations, nav_mesh)
+        {
+            search.status = SearchStatus::Failed;
+        }
+
+        let status = self.step_search(&mut search, nav_mesh, filter, max_iterations);
+        self.search = Some(search);
+
+        match status {
+            SearchStatus::InProgress => Ok(SlicedFindPathStatus::InProgress),
+            SearchStatus::Finished => Ok(SlicedFindPathStatus::Finished),
+            SearchStatus::Failed => Err(SlicedFindPathError::NavMeshChanged),
+        }
+    }
+
+    /// Finishes the sliced search & returns the polygons crossed as a [PolygonPath].
+    ///
+    /// If the search hasn't finished this returns a [PathStatus::Partial] path to the polygon closest to the destination found so far.
+    /// Fails with [SlicedFindPathError::NavMeshChanged] if a tile the search has visited changed since the last update.
+    pub fn finalize_sliced_find_path(
+        &mut self,
+        nav_mesh: &NavMeshTiles,
+    ) -> Result<PolygonPath, SlicedFindPathError> {
+        let Some(search) = self.search.take() else {
+            return Err(SlicedFindPathError::NotInitialized);
+        };
+        // The path's polygon references are stamped with the current tile salts, so they'd point into the changed tiles.
+        if search.status == SearchStatus::Failed
+            || visited_tiles_changed(&self.tile_generations, nav_mesh)
+        {
+            return Err(SlicedFindPathError::NavMeshChanged);
+        }
+
+        let mut polygons = Vec::new();
+        let status = self.finish_search(nav_mesh, search, &mut polygons);
+
+        Ok(PolygonPath { polygons, status })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn find_polygon_path_into(
+        &mut self,
+        nav_mesh: &NavMeshTiles,
+        nav_mesh_settings: &NavMeshSettings,
+        start_pos: Vec3,
+        end_pos: Vec3,
+        position_search_extents: Option<Vec3>,
+        filter: Option<&dyn QueryFilter>,
+        path: &mut Vec<PolyRef>,
+    ) -> Result<PathStatus, FindPolygonPathError> {
+        path.clear();
+
+        let mut search = self.init_search(
+            nav_mesh,
+            nav_mesh_settings,
+            start_pos,
+            end_pos,
+            position_search_extents,
+            filter,
+        )?;
+
+        self.step_search(&mut search, nav_mesh, filter, usize::MAX);
+
+        Ok(self.finish_search(nav_mesh, search, path))
+    }
+
+    /// Ends ``search``, writing the path to the best node found to ``path`` & returning how far it got.
+    fn finish_search(
+        &self,
+        nav_mesh: &NavMeshTiles,
+        search: SearchState,
+        path: &mut Vec<PolyRef>,
+    ) -> PathStatus {
+        self.write_polygon_path(nav_mesh, search.last_best_node, path);
+
+        let best_node = &self.nodes[search.last_best_node];
+        if best_node.tile == search.end_tile && best_node.polygon == search.end_polygon {
+            return PathStatus::Complete;
+        }
+
+        let closest_point = nav_mesh
+            .tiles
+            .get(&best_node.tile)
+            .and_then(|tile| {
+                tile.polygons
+                    .get(best_node.polygon as usize)
+                    .map(|polygon| tile.get_closest_point_in_polygon(polygon, search.end_pos))
+            })
+            .unwrap_or(best_node.position);
+
+        if search.out_of_nodes {
+            PathStatus::OutOfNodes { closest_point }
+        } else {
+            PathStatus::Partial { closest_point }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.nodes.clear();
+        self.node_lookup.clear();
+        self.open_list.clear();
+        self.search = None;
+        self.tile_generations.clear();
+    }
+
+    /// Finds the start & end polygons & sets up the start node for a new search. Clears any sliced search in progress.
+    fn init_search(
+        &mut self,
+        nav_mesh: &NavMeshTiles,
+        nav_mesh_settings: &NavMeshSettings,
+        start_pos: Vec3,
+        end_pos: Vec3,
+        position_search_extents: Option<Vec3>,
+        filter: Option<&dyn QueryFilter>,
+    ) -> Result<SearchState, FindPolygonPathError> {
+        self.reset();
+
+        let (start, end) = find_search_polygons(
+            nav_mesh,
+            nav_mesh_settings,
+            start_pos,
+            end_pos,
+            position_search_extents,
+            filter,
+        )?;
+
+        Ok(self.init_search_from_polygons(nav_mesh, start, end))
+    }
+
+    /// Sets up the start node for a new search between two known polygons. Clears any sliced search in progress.
+    fn init_search_from_polygons(
+        &mut self,
+        nav_mesh: &NavMeshTiles,
+        (start, start_pos): (PolyRef, Vec3),
+        (end, end_pos): (PolyRef, Vec3),
+    ) -> SearchState {
+        self.reset();
+
+        let (start_tile, start_poly) = (start.tile(), start.polygon());
 
-    {
         let start_node = NavMeshNode {
             position: start_pos,
             cost: 0.0,
@@ -88,128 +769,218 @@ pub fn find_polygon_path(
             parent: None,
         };
 
-        nodes.push(start_node);
-        open_list.push(0);
-    }
+        let search = SearchState {
+            end_tile: end.tile(),
+            end_polygon: end.polygon(),
+            end_pos,
+            last_best_node: 0,
+            last_best_node_cost: start_node.total_cost,
+            out_of_nodes: false,
+            status: if start == end {
+                SearchStatus::Finished
+            } else {
+                SearchStatus::InProgress
+            },
+        };
 
-    let mut last_best_node = 0;
-    let mut last_best_node_cost = nodes[0].total_cost;
+        self.open_list.push(OpenListEntry {
+            total_cost: start_node.total_cost,
+            node: 0,
+        });
+        self.node_lookup.insert((start_tile, start_poly), 0);
+        self.nodes.push(start_node);
+        self.tile_generations.insert(
+            start_tile,
+            *nav_mesh.tile_generations.get(&start_tile).unwrap_or(&0),
+        );
+
+        search
+    }
 
-    while let Some(best_node_index) = open_list.pop() {
-        let (best_tile, best_polygon, best_position, best_cost, best_parent) = {
-            let node = &mut nodes[best_node_index];
-            node.state = NodeState::Closed;
+    /// Searches from ``start`` toward ``end`` for at most ``max_iterations`` iterations.
+    ///
+    /// Returns the path to the furthest polygon in ``existing`` that the search reached. Empty if none of them were reached.
+    pub(crate) fn find_path_to_furthest_existing(
+        &mut self,
+        nav_mesh: &NavMeshTiles,
+        start: (PolyRef, Vec3),
+        end: (PolyRef, Vec3),
+        existing: &[PolyRef],
+        max_iterations: usize,
+        filter: Option<&dyn QueryFilter>,
+    ) -> Vec<PolyRef> {
+        let mut search = self.init_search_from_polygons(nav_mesh, start, end);
+        self.step_search(&mut search, nav_mesh, filter, max_iterations);
+
+        let mut path = Vec::new();
+        if let Some(node) = existing
+            .iter()
+            .rev()
+            .find_map(|polygon| self.node_lookup.get(&(polygon.tile(), polygon.polygon())))
+        {
+            self.write_polygon_path(nav_mesh, *node, &mut path);
+        }
 
-            if node.tile == end_tile && node.polygon == end_poly {
-                last_best_node = best_node_index;
-                break;
-            }
+        path
+    }
 
-            (
-                node.tile,
-                node.polygon,
-                node.position,
-                node.cost,
-                node.parent,
-            )
-        };
+    /// Runs at most ``max_iterations`` iterations of A* on ``search``.
+    fn step_search(
+        &mut self,
+        search: &mut SearchState,
+        nav_mesh: &NavMeshTiles,
+        filter: Option<&dyn QueryFilter>,
+        max_iterations: usize,
+    ) -> SearchStatus {
+        let filter = filter.unwrap_or(&PassAllFilter);
+
+        let Self {
+            max_nodes,
+            nodes,
+            node_lookup,
+            open_list,
+            tile_generations,
+            ..
+        } = self;
+
+        if search.status != SearchStatus::InProgress {
+            return search.status;
+        }
 
-        let node_tile = nav_mesh.tiles.get(&best_tile).unwrap();
-
-        for link in node_tile.polygons[best_polygon as usize].links.iter() {
-            let (link_tile, link_polygon) = match link {
-                Link::Internal {
-                    neighbour_polygon, ..
-                } => (best_tile, *neighbour_polygon),
-                Link::External {
-                    neighbour_polygon,
-                    direction,
-                    ..
-                } => (direction.offset(best_tile), *neighbour_polygon),
+        let max_nodes = max_nodes.map_or(usize::MAX, NonZeroUsize::get);
+
+        let end_tile = search.end_tile;
+        let end_poly = search.end_polygon;
+        let end_pos = search.end_pos;
+
+        let mut iterations = 0;
+        while iterations < max_iterations {
+            let Some(OpenListEntry {
+                total_cost: entry_cost,
+                node: best_node_index,
+            }) = open_list.pop()
+            else {
+                // Open list exhausted, the destination can't be reached.
+                search.status = SearchStatus::Finished;
+                break;
             };
 
-            // Don't go back to our parent.
-            if let Some(parent) = best_parent {
-                if nodes[parent].tile == link_tile && nodes[parent].polygon == link_polygon {
+            let (best_tile, best_polygon, best_position, best_cost, best_parent) = {
+                let node = &mut nodes[best_node_index];
+
+                // Entries are never removed from the heap when a node is updated. Skip any stale ones.
+                if node.state != NodeState::Open || node.total_cost != entry_cost {
                     continue;
                 }
-            }
+                node.state = NodeState::Closed;
 
-            let neighbour_node_index = if let Some(index) = nodes
-                .iter()
-                .position(|element| element.tile == link_tile && element.polygon == link_polygon)
-            {
-                index
-            } else {
-                // Node hasn't been visited already, let's create it.
-                let position = match link {
-                    Link::Internal { edge, .. } => {
-                        // Just the midpoint of the current edge.
-                        let indices = &node_tile.polygons[best_polygon as usize].indices;
-                        let a = node_tile.vertices[indices[*edge as usize] as usize];
-                        let b = node_tile.vertices
-                            [indices[(*edge + 1) as usize % indices.len()] as usize];
-
-                        a.lerp(b, 0.5)
-                    }
-                    Link::External {
-                        edge,
-                        bound_min,
-                        bound_max,
-                        ..
-                    } => {
-                        // The mid point of the current-edge sliced by bound_min & bound_max.
-                        let indices = &node_tile.polygons[best_polygon as usize].indices;
-                        let a = node_tile.vertices[indices[*edge as usize] as usize];
-                        let b = node_tile.vertices
-                            [indices[(*edge + 1) as usize % indices.len()] as usize];
-
-                        const S: f32 = 1.0 / 255.0;
-                        let bound_min = *bound_min as f32 * S;
-                        let bound_max = *bound_max as f32 * S;
-                        let clamped_a = a.lerp(b, bound_min);
-                        let clamped_b = a.lerp(b, bound_max);
-
-                        clamped_a.lerp(clamped_b, 0.5)
+                if node.tile == end_tile && node.polygon == end_poly {
+                    search.last_best_node = best_node_index;
+                    search.status = SearchStatus::Finished;
+                    break;
+                }
+
+                (
+                    node.tile,
+                    node.polygon,
+                    node.position,
+                    node.cost,
+                    node.parent,
+                )
+            };
+            iterations += 1;
+
+            let Some(node_tile) = nav_mesh.tiles.get(&best_tile) else {
+                search.status = SearchStatus::Failed;
+                break;
+            };
+            let Some(best_poly) = node_tile.polygons.get(best_polygon as usize) else {
+                search.status = SearchStatus::Failed;
+                break;
+            };
+
+            let best_area = node_tile.areas[best_polygon as usize];
+
+            for link in best_poly.links.iter() {
+                let (link_tile, link_polygon) = link.neighbour(best_tile);
+
+                // Don't go back to our parent.
+                if let Some(parent) = best_parent {
+                    if nodes[parent].tile == link_tile && nodes[parent].polygon == link_polygon {
+                        continue;
                     }
+                }
+
+                let neighbour_tile = if link_tile == best_tile {
+                    Some(node_tile)
+                } else {
+                    nav_mesh.tiles.get(&link_tile)
+                };
+                let Some(neighbour_tile) = neighbour_tile else {
+                    continue;
                 };
 
-                nodes.push(NavMeshNode {
-                    position,
-                    cost: 0.0,
-                    total_cost: 0.0,
-                    tile: link_tile,
-                    polygon: link_polygon,
-                    state: NodeState::Unchecked,
-                    parent: None,
-                });
+                if !neighbour_tile.polygon_passes_filter(link_tile, link_polygon, filter) {
+                    continue;
+                }
+                let neighbour_area = neighbour_tile.areas[link_polygon as usize];
+
+                let neighbour_node_index = match node_lookup.entry((link_tile, link_polygon)) {
+                    Entry::Occupied(entry) => *entry.get(),
+                    Entry::Vacant(entry) => {
+                        if nodes.len() >= max_nodes {
+                            // Node pool is exhausted, we can't explore any further from here.
+                            search.out_of_nodes = true;
+                            continue;
+                        }
 
-                nodes.len() - 1
-            };
+                        // Node hasn't been visited already, let's create it.
+                        // The position is the mid point of the portal between the polygons.
+                        let (portal_a, portal_b) = node_tile.get_link_portal(best_poly, link);
+                        let position = portal_a.lerp(portal_b, 0.5);
+
+                        nodes.push(NavMeshNode {
+                            position,
+                            cost: 0.0,
+                            total_cost: 0.0,
+                            tile: link_tile,
+                            polygon: link_polygon,
+                            state: NodeState::Unchecked,
+                            parent: None,
+                        });
+
+                        tile_generations.entry(link_tile).or_insert_with(|| {
+                            *nav_mesh.tile_generations.get(&link_tile).unwrap_or(&0)
+                        });
+
+                        *entry.insert(nodes.len() - 1)
+                    }
+                };
 
-            let (old_state, total_cost) = {
                 let neighbour_node = &mut nodes[neighbour_node_index];
 
-                // TODO: Ideally you want to be able to override this but for now we just go with the distance.
-                let node_cost_multiplier = area_cost_multipliers.map_or(1.0, |multipliers| {
-                    let area = nav_mesh.tiles[&best_tile].areas[best_polygon as usize];
-
-                    *multipliers.get(area.0 as usize).unwrap_or(&1.0)
-                });
+                let current_cost = filter.cost(
+                    best_position,
+                    neighbour_node.position,
+                    best_area,
+                    Some(link),
+                );
+                if !current_cost.is_finite() {
+                    continue;
+                }
 
                 let (cost, heuristic) = if end_tile == link_tile && end_poly == link_polygon {
                     // Special case for the final node.
-                    let current_cost =
-                        best_position.distance(neighbour_node.position) * node_cost_multiplier;
-                    let end_cost = neighbour_node.position.distance(end_pos);
+                    let end_cost =
+                        filter.cost(neighbour_node.position, end_pos, neighbour_area, None);
+                    if !end_cost.is_finite() {
+                        continue;
+                    }
 
                     let cost = best_cost + current_cost + end_cost;
 
                     (cost, 0.0)
                 } else {
-                    let current_cost =
-                        best_position.distance(neighbour_node.position) * node_cost_multiplier;
-
                     let cost = best_cost + current_cost;
                     let heuristic = neighbour_node.position.distance(end_pos) * HEURISTIC_SCALE;
 
@@ -223,108 +994,363 @@ pub fn find_polygon_path(
                     continue;
                 }
 
-                let old_state = neighbour_node.state;
                 neighbour_node.parent = Some(best_node_index);
                 neighbour_node.state = NodeState::Open;
                 neighbour_node.cost = cost;
                 neighbour_node.total_cost = total_cost;
 
-                if heuristic < last_best_node_cost {
-                    last_best_node_cost = heuristic;
-                    last_best_node = neighbour_node_index;
-                }
-
-                (old_state, total_cost)
-            };
-
-            if old_state == NodeState::Open {
-                // Node already exists. Let's remove it.
-                if let Some(existing_index) = open_list
-                    .iter()
-                    .position(|node| *node == neighbour_node_index)
-                {
-                    open_list.remove(existing_index);
+                if heuristic < search.last_best_node_cost {
+                    search.last_best_node_cost = heuristic;
+                    search.last_best_node = neighbour_node_index;
                 }
-            }
 
-            // We want to insert the node into the list so that the next entry has a lower total.
-            if let Some(index) = open_list
-                .iter()
-                .position(|node_index| nodes[*node_index].total_cost < total_cost)
-            {
-                open_list.insert(index, neighbour_node_index);
-            } else {
-                // There is no entry with a lower total.
-                open_list.push(neighbour_node_index);
+                // If the node was already open its previous entry is now stale & will be skipped.
+                open_list.push(OpenListEntry {
+                    total_cost,
+                    node: neighbour_node_index,
+                });
             }
         }
+
+        search.status
     }
 
-    // Is this worth it? :shrug: It saves some memory allocations which I consider important. All locations should also be pretty hot in cache in the next loop.
-    let path_count = {
-        let mut count = 0;
-        let mut parent = Some(last_best_node);
+    /// Walks the parents of ``node`` back to the start, writing the path to ``path`` in start to ``node`` order.
+    fn write_polygon_path(&self, nav_mesh: &NavMeshTiles, node: usize, path: &mut Vec<PolyRef>) {
+        let mut parent = Some(node);
         while let Some(parent_index) = parent {
-            count += 1;
-            parent = nodes[parent_index].parent;
-        }
-
-        count
-    };
-
-    let mut path = Vec::with_capacity(path_count);
+            let node = &self.nodes[parent_index];
 
-    let mut parent = Some(last_best_node);
-    while let Some(parent_index) = parent {
-        let node = &nodes[parent_index];
+            path.push(nav_mesh.poly_ref_unchecked(node.tile, node.polygon));
 
-        path.push((node.tile, node.polygon));
+            parent = node.parent;
+        }
 
-        parent = node.parent;
+        path.reverse();
     }
-
-    path.reverse();
-
-    Ok(path)
 }
 
-#[derive(Debug)]
-pub enum StringPullingError {
-    PathEmpty,
-    MissingStartTile,
-    MissingEndTile,
-    MissingNodeTile,
-    NoLinkBetweenPathPoints,
+/// Returns true if any tile in ``tile_generations`` has a different generation in ``nav_mesh``, i.e. it has been rebuilt or removed.
+fn visited_tiles_changed(tile_generations: &HashMap<UVec2, u64>, nav_mesh: &NavMeshTiles) -> bool {
+    tile_generations.iter().any(|(tile_coord, generation)| {
+        nav_mesh.tile_generations.get(tile_coord).unwrap_or(&0) != generation
+    })
 }
 
-/// Performs "string pulling" on a path of polygons. Used to convert [find_path]'s result to a world space path.
+/// Performs A* pathfinding on the supplied nav-mesh.
+/// Returning the polygons crossed as a [PolygonPath] or [FindPolygonPathError]
 ///
-/// Returns the path as `Vec<Vec3>` or [StringPullingError]
-pub fn perform_string_pulling_on_path(
-    nav_mesh: &NavMeshTiles,
-    start_pos: Vec3,
-    end_pos: Vec3,
-    path: &[(UVec2, u16)],
-) -> Result<Vec<Vec3>, StringPullingError> {
-    if path.is_empty() {
-        return Err(StringPullingError::PathEmpty);
+/// If the destination can't be reached the path leads to the polygon closest to it, see [PolygonPath::status].
+///
+/// Use [NavMeshQuery::find_polygon_path] to reuse allocations between queries.
+///
+/// * ``nav_mesh`` - Nav-mesh to pathfind across.
+/// * ``nav_mesh_settings`` - Nav-mesh settings used to generate ``nav_mesh``.
+/// * ``start_pos`` - Starting position for the path.
+/// * ``end_pos`` - Destination position for the path, i.e where you want to go.
+/// * ``position_search_extents`` - Half extents of the box to search for a start & end polygon in. In world units. See [NavMeshTiles::project_point]. If **``None``** is supplied [DEFAULT_POSITION_SEARCH_EXTENTS] is used.
+/// * ``filter`` - Filter deciding which polygons may be traversed & at what cost. Use to prioritize, deprioritize or forbid taking certain paths. Also used when finding the start & end polygons. If **``None``** is supplied every polygon may be traversed & the cost is the distance travelled. See [DefaultQueryFilter].
+pub fn find_polygon_path(
+    nav_mesh: &NavMeshTiles,
+    nav_mesh_settings: &NavMeshSettings,
+    start_pos: Vec3,
+    end_pos: Vec3,
+    position_search_extents: Option<Vec3>,
+    filter: Option<&dyn QueryFilter>,
+) -> Result<PolygonPath, FindPolygonPathError> {
+    NavMeshQuery::default().find_polygon_path(
+        nav_mesh,
+        nav_mesh_settings,
+        start_pos,
+        end_pos,
+        position_search_extents,
+        filter,
+    )
+}
+
+/// Performs hierarchical pathfinding on the supplied nav-mesh.
+/// Returning a [HierarchicalPath] or [FindPolygonPathError]
+///
+/// Searches the nav-mesh's [crate::hierarchy::AbstractGraph] for which tile borders to cross & then only runs A* up to the first crossing.
+/// The rest is refined one crossing at a time with [NavMeshQuery::refine_hierarchical_path] as it's needed, so long paths never search every polygon along the way up front.
+/// This visits far fewer polygons than [find_polygon_path] on large nav-meshes, at the cost of the path not always being the cheapest.
+/// Falls back to [find_polygon_path] when the start & end are in the same tile, aren't connected (see [NavMeshTiles::are_connected]) or no path is found across the abstract graph.
+///
+/// Use [NavMeshQuery::find_polygon_path_hierarchical] to reuse allocations between queries.
+///
+/// See [find_polygon_path] for a description of the parameters.
+pub fn find_polygon_path_hierarchical(
+    nav_mesh: &NavMeshTiles,
+    nav_mesh_settings: &NavMeshSettings,
+    start_pos: Vec3,
+    end_pos: Vec3,
+    position_search_extents: Option<Vec3>,
+    filter: Option<&dyn QueryFilter>,
+) -> Result<HierarchicalPath, FindPolygonPathError> {
+    NavMeshQuery::default().find_polygon_path_hierarchical(
+        nav_mesh,
+        nav_mesh_settings,
+        start_pos,
+        end_pos,
+        position_search_extents,
+        filter,
+    )
+}
+
+/// Finds the start & end polygons for a search as (polygon, position).
+#[allow(clippy::type_complexity)]
+fn find_search_polygons(
+    nav_mesh: &NavMeshTiles,
+    nav_mesh_settings: &NavMeshSettings,
+    start_pos: Vec3,
+    end_pos: Vec3,
+    position_search_extents: Option<Vec3>,
+    filter: Option<&dyn QueryFilter>,
+) -> Result<((PolyRef, Vec3), (PolyRef, Vec3)), FindPolygonPathError> {
+    let search_extents = position_search_extents.unwrap_or(DEFAULT_POSITION_SEARCH_EXTENTS);
+
+    let Some(start) = nav_mesh.project_point(nav_mesh_settings, start_pos, search_extents, filter)
+    else {
+        return Err(FindPolygonPathError::NoValidStartPolygon);
+    };
+
+    let Some(end) = nav_mesh.project_point(nav_mesh_settings, end_pos, search_extents, filter)
+    else {
+        return Err(FindPolygonPathError::NoValidEndPolygon);
+    };
+
+    Ok(((start.polygon, start.position), (end.polygon, end.position)))
+}
+
+/// Flags describing why a [StraightPathPoint] exists.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StraightPathFlags(pub u8);
+impl StraightPathFlags {
+    /// The point is the start of the path.
+    pub const START: Self = Self(1 << 0);
+    /// The point is the end of the path.
+    pub const END: Self = Self(1 << 1);
+    /// The point is on a portal where the area type changes.
+    pub const AREA_CHANGE: Self = Self(1 << 2);
+    /// The point is on a portal between two tiles.
+    pub const TILE_CROSSING: Self = Self(1 << 3);
+
+    /// Returns true if all flags in ``other`` are set.
+    #[inline]
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+impl std::ops::BitOr for StraightPathFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+impl std::ops::BitOrAssign for StraightPathFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// A point along a [StraightPath].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StraightPathPoint {
+    pub position: Vec3,
+    pub flags: StraightPathFlags,
+    /// The polygon the segment following this point starts in. For the end point this is the last polygon of the path.
+    pub polygon: PolyRef,
+    /// Area type of the segment following this point.
+    pub area: Area,
+}
+
+/// World space path returned by [find_straight_path].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StraightPath {
+    pub points: Vec<StraightPathPoint>,
+}
+
+/// Options for [find_straight_path].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StraightPathOptions {
+    /// Add a point wherever the path crosses into a polygon of a different area type.
+    pub area_crossings: bool,
+    /// Add a point wherever the path crosses into another tile.
+    pub tile_crossings: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum StringPullingError {
+    PathEmpty,
+    MissingStartTile,
+    MissingEndTile,
+    MissingNodeTile,
+    NoLinkBetweenPathPoints,
+    /// A polygon in the path no longer exists or its tile has been rebuilt since the path was found.
+    InvalidPolygonRef,
+}
+
+/// Performs "string pulling" on a path of polygons. Used to convert [find_polygon_path]'s result to a world space path.
+///
+/// Returns the path as `Vec<Vec3>` or [StringPullingError]. Use [find_straight_path] to also know which polygon each point is on.
+pub fn perform_string_pulling_on_path(
+    nav_mesh: &NavMeshTiles,
+    start_pos: Vec3,
+    end_pos: Vec3,
+    path: &[PolyRef],
+) -> Result<Vec<Vec3>, StringPullingError> {
+    let straight_path = find_straight_path(
+        nav_mesh,
+        start_pos,
+        end_pos,
+        path,
+        StraightPathOptions::default(),
+    )?;
+
+    Ok(straight_path
+        .points
+        .into_iter()
+        .map(|point| point.position)
+        .collect())
+}
+
+/// Performs "string pulling" on a path of polygons, keeping track of the polygon, area type & reason behind each point.
+///
+/// Returns the path as a [StraightPath] or [StringPullingError]
+///
+/// * ``nav_mesh`` - Nav-mesh the path was found on.
+/// * ``start_pos`` - Start position of the path. Clamped to the first polygon.
+/// * ``end_pos`` - End position of the path. Clamped to the last polygon.
+/// * ``path`` - Polygon path, for example from [find_polygon_path].
+/// * ``options`` - Which extra points to add where the path crosses area types or tiles.
+pub fn find_straight_path(
+    nav_mesh: &NavMeshTiles,
+    start_pos: Vec3,
+    end_pos: Vec3,
+    path: &[PolyRef],
+    options: StraightPathOptions,
+) -> Result<StraightPath, StringPullingError> {
+    find_straight_path_limited(nav_mesh, start_pos, end_pos, path, options, usize::MAX)
+}
+
+/// [find_straight_path] stopping once ``max_points`` points have been found.
+pub(crate) fn find_straight_path_limited(
+    nav_mesh: &NavMeshTiles,
+    start_pos: Vec3,
+    end_pos: Vec3,
+    path: &[PolyRef],
+    options: StraightPathOptions,
+    max_points: usize,
+) -> Result<StraightPath, StringPullingError> {
+    if path.is_empty() {
+        return Err(StringPullingError::PathEmpty);
     }
 
-    let Some(start_tile) = nav_mesh.tiles.get(&path[0].0) else {
+    let Some(start_tile) = nav_mesh.tiles.get(&path[0].tile()) else {
         return Err(StringPullingError::MissingStartTile);
     };
-    let Some(end_tile) = nav_mesh.tiles.get(&path.last().unwrap().0) else {
+    let Some(end_tile) = nav_mesh.tiles.get(&path.last().unwrap().tile()) else {
         return Err(StringPullingError::MissingEndTile);
     };
+    if !path
+        .iter()
+        .all(|polygon| nav_mesh.is_valid_poly_ref(*polygon))
+    {
+        return Err(StringPullingError::InvalidPolygonRef);
+    }
 
     let start_pos = start_tile
-        .get_closest_point_in_polygon(&start_tile.polygons[path[0].1 as usize], start_pos);
-    let end_pos = end_tile
-        .get_closest_point_in_polygon(&end_tile.polygons[path.last().unwrap().1 as usize], end_pos);
+        .get_closest_point_in_polygon(&start_tile.polygons[path[0].polygon() as usize], start_pos);
+    let end_pos = end_tile.get_closest_point_in_polygon(
+        &end_tile.polygons[path.last().unwrap().polygon() as usize],
+        end_pos,
+    );
+
+    // Portal between each polygon & the next along with the area of every polygon. The last portal is the end position.
+    let mut portals = Vec::with_capacity(path.len());
+    let mut areas = Vec::with_capacity(path.len());
+    for (i, current) in path.iter().enumerate() {
+        let Some(node_tile) = nav_mesh.tiles.get(&current.tile()) else {
+            return Err(StringPullingError::MissingNodeTile);
+        };
+        let polygon = &node_tile.polygons[current.polygon() as usize];
+        areas.push(node_tile.areas[current.polygon() as usize]);
+
+        if let Some(next) = path.get(i + 1) {
+            // Find link between this and next in path.
+            let Some(link) = polygon
+                .links
+                .iter()
+                .find(|link| link.neighbour(current.tile()) == (next.tile(), next.polygon()))
+            else {
+                return Err(StringPullingError::NoLinkBetweenPathPoints);
+            };
+
+            portals.push(node_tile.get_link_portal(polygon, link));
+        } else {
+            portals.push((end_pos, end_pos));
+        }
+    }
 
-    let mut string_path = Vec::with_capacity(path.len() / 3 + 2);
-    string_path.push(start_pos);
+    // Flags of the portal between ``path[i]`` & ``path[i + 1]``.
+    let portal_flags = |i: usize| {
+        let mut flags = StraightPathFlags::default();
+        if let Some(next) = path.get(i + 1) {
+            if areas[i] != areas[i + 1] {
+                flags |= StraightPathFlags::AREA_CHANGE;
+            }
+            if path[i].tile() != next.tile() {
+                flags |= StraightPathFlags::TILE_CROSSING;
+            }
+        }
+
+        flags
+    };
+    // Point on the portal at ``portal_index``.
+    let make_point = |position: Vec3, flags: StraightPathFlags, portal_index: usize| {
+        let polygon_index = (portal_index + 1).min(path.len() - 1);
+
+        StraightPathPoint {
+            position,
+            flags: flags | portal_flags(portal_index),
+            polygon: path[polygon_index],
+            area: areas[polygon_index],
+        }
+    };
+    // Adds points where the segment from the last point to ``end`` crosses the portals in ``start_index..end_index``.
+    let append_portals =
+        |points: &mut Vec<StraightPathPoint>, start_index: usize, end_index: usize, end: Vec3| {
+            let start = points.last().unwrap().position;
+
+            for (i, (left, right)) in portals.iter().enumerate().take(end_index).skip(start_index) {
+                let flags = portal_flags(i);
+                if !(options.area_crossings && flags.contains(StraightPathFlags::AREA_CHANGE)
+                    || options.tile_crossings && flags.contains(StraightPathFlags::TILE_CROSSING))
+                {
+                    continue;
+                }
+
+                let Some((_, t)) = intersect_segments_2d(start, end, *left, *right) else {
+                    continue;
+                };
 
+                append_straight_path_point(
+                    points,
+                    make_point(left.lerp(*right, t), StraightPathFlags::default(), i),
+                );
+            }
+        };
+
+    let mut points = Vec::with_capacity(path.len() / 3 + 2);
+    points.push(StraightPathPoint {
+        position: start_pos,
+        flags: StraightPathFlags::START,
+        polygon: path[0],
+        area: areas[0],
+    });
+
+    let mut apex_index = 0;
     if path.len() > 1 {
         let mut portal_apex = start_pos;
         let mut portal_left = start_pos;
@@ -335,65 +1361,7 @@ pub fn perform_string_pulling_on_path(
 
         let mut i = 0;
         while i < path.len() {
-            let (left, right) = if let Some(next) = path.get(i + 1) {
-                let current = &path[i];
-                // Find link between this and next in path.
-                let Some(node_tile) = nav_mesh.tiles.get(&current.0) else {
-                    return Err(StringPullingError::MissingNodeTile);
-                };
-                let is_internal = current.0 == next.0;
-                let Some(link) = node_tile.polygons[current.1 as usize]
-                    .links
-                    .iter()
-                    .find(|link| {
-                        // This is a mess :)))
-                        match link {
-                            Link::Internal {
-                                neighbour_polygon, ..
-                            } => is_internal && next.1 == *neighbour_polygon,
-                            Link::External {
-                                neighbour_polygon,
-                                direction,
-                                ..
-                            } => {
-                                direction.offset(current.0) == next.0
-                                    && next.1 == *neighbour_polygon
-                            }
-                        }
-                    })
-                else {
-                    return Err(StringPullingError::NoLinkBetweenPathPoints);
-                };
-
-                let indices = &node_tile.polygons[current.1 as usize].indices;
-                match link {
-                    Link::Internal { edge, .. } => {
-                        let a = node_tile.vertices[indices[*edge as usize] as usize];
-                        let b = node_tile.vertices
-                            [indices[(*edge + 1) as usize % indices.len()] as usize];
-
-                        (a, b)
-                    }
-                    Link::External {
-                        edge,
-                        bound_min,
-                        bound_max,
-                        ..
-                    } => {
-                        let a = node_tile.vertices[indices[*edge as usize] as usize];
-                        let b = node_tile.vertices
-                            [indices[(*edge + 1) as usize % indices.len()] as usize];
-
-                        const S: f32 = 1.0 / 255.0;
-                        let clamped_a = a.lerp(b, *bound_min as f32 * S);
-                        let clamped_b = a.lerp(b, *bound_max as f32 * S);
-
-                        (clamped_a, clamped_b)
-                    }
-                }
-            } else {
-                (end_pos, end_pos)
-            };
+            let (left, right) = portals[i];
 
             // Right vertex.
             if triangle_area_2d(portal_apex, portal_right, right) <= 0.0 {
@@ -403,10 +1371,18 @@ pub fn perform_string_pulling_on_path(
                     portal_right = right;
                     right_index = i;
                 } else {
-                    portal_apex = portal_left;
+                    append_portals(&mut points, apex_index, left_index, portal_left);
 
-                    if *string_path.last().unwrap() != portal_apex {
-                        string_path.push(portal_apex);
+                    portal_apex = portal_left;
+                    apex_index = left_index;
+
+                    append_straight_path_point(
+                        &mut points,
+                        make_point(portal_apex, StraightPathFlags::default(), apex_index),
+                    );
+                    if points.len() >= max_points {
+                        points.truncate(max_points);
+                        return Ok(StraightPath { points });
                     }
 
                     portal_left = portal_apex;
@@ -426,10 +1402,18 @@ pub fn perform_string_pulling_on_path(
                     portal_left = left;
                     left_index = i;
                 } else {
-                    portal_apex = portal_right;
+                    append_portals(&mut points, apex_index, right_index, portal_right);
 
-                    if *string_path.last().unwrap() != portal_apex {
-                        string_path.push(portal_apex);
+                    portal_apex = portal_right;
+                    apex_index = right_index;
+
+                    append_straight_path_point(
+                        &mut points,
+                        make_point(portal_apex, StraightPathFlags::default(), apex_index),
+                    );
+                    if points.len() >= max_points {
+                        points.truncate(max_points);
+                        return Ok(StraightPath { points });
                     }
 
                     portal_left = portal_apex;
@@ -445,48 +1429,915 @@ pub fn perform_string_pulling_on_path(
         }
     }
 
-    if *string_path.last().unwrap() != end_pos {
-        string_path.push(end_pos);
+    append_portals(&mut points, apex_index, path.len() - 1, end_pos);
+    append_straight_path_point(
+        &mut points,
+        StraightPathPoint {
+            position: end_pos,
+            flags: StraightPathFlags::END,
+            polygon: *path.last().unwrap(),
+            area: *areas.last().unwrap(),
+        },
+    );
+    points.truncate(max_points);
+
+    Ok(StraightPath { points })
+}
+
+/// Pushes ``point`` unless it is at the same position as the last point, in which case the flags are merged into the last point.
+fn append_straight_path_point(points: &mut Vec<StraightPathPoint>, point: StraightPathPoint) {
+    if let Some(last) = points.last_mut() {
+        if last.position == point.position {
+            last.flags |= point.flags;
+            return;
+        }
     }
 
-    Ok(string_path)
+    points.push(point);
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum FindPathError {
     PolygonPath(FindPolygonPathError),
     StringPulling(StringPullingError),
 }
 
 /// Performs A* pathfinding and string pulling on the supplied nav-mesh.
-/// Returns the path as `Vec<Vec3>` or [FindPathError]
+/// Returns the path as a [Path] or [FindPathError]
+///
+/// If the destination can't be reached the path ends at the closest reachable point, see [Path::status].
+///
+/// Use [NavMeshQuery::find_path] to reuse allocations between queries.
 ///
 /// * ``nav_mesh`` - Nav-mesh to pathfind across.
 /// * ``nav_mesh_settings`` - Nav-mesh settings used to generate ``nav_mesh``.
 /// * ``start_pos`` - Starting position for the path.
 /// * ``end_pos`` - Destination position for the path, i.e where you want to go.
-/// * ``position_search_radius`` - Radius to search for a start & end polygon in. In world units. If **``None``** is supplied a default value of ``5.0`` is used.
-/// * ``area_cost_multipliers`` - Multipliers for area cost, use to prioritize or deprioritize taking certain paths. Values not present default to 1.0. Lesser value means the path costs less.
+/// * ``position_search_extents`` - Half extents of the box to search for a start & end polygon in. In world units. See [NavMeshTiles::project_point]. If **``None``** is supplied [DEFAULT_POSITION_SEARCH_EXTENTS] is used.
+/// * ``filter`` - Filter deciding which polygons may be traversed & at what cost. Use to prioritize, deprioritize or forbid taking certain paths. Also used when finding the start & end polygons. If **``None``** is supplied every polygon may be traversed & the cost is the distance travelled. See [DefaultQueryFilter].
 pub fn find_path(
     nav_mesh: &NavMeshTiles,
     nav_mesh_settings: &NavMeshSettings,
     start_pos: Vec3,
     end_pos: Vec3,
-    position_search_radius: Option<f32>,
-    area_cost_multipliers: Option<&[f32]>,
-) -> Result<Vec<Vec3>, FindPathError> {
-    match find_polygon_path(
+    position_search_extents: Option<Vec3>,
+    filter: Option<&dyn QueryFilter>,
+) -> Result<Path, FindPathError> {
+    NavMeshQuery::default().find_path(
         nav_mesh,
         nav_mesh_settings,
         start_pos,
         end_pos,
-        position_search_radius,
-        area_cost_multipliers,
-    ) {
-        Ok(path) => perform_string_pulling_on_path(nav_mesh, start_pos, end_pos, &path)
-            .map_err(FindPathError::StringPulling),
-        Err(error) => Err(FindPathError::PolygonPath(error)),
+        position_search_extents,
+        filter,
+    )
+}
+
+/// Wall hit by a [raycast] before it reached its end position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaycastWallHit {
+    /// Fraction along the ray (``start_pos`` -> ``end_pos``) at which the wall was hit. In the range ``[0.0, 1.0]``.
+    pub t: f32,
+    /// Distance from ``start_pos`` to the hit on the XZ-plane. In world units.
+    pub distance: f32,
+    /// Normal of the wall edge that was hit. Lies on the XZ-plane & faces back toward ``start_pos``.
+    pub normal: Vec3,
+}
+
+/// Result of a [raycast].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RaycastResult {
+    /// The wall the ray hit, **``None``** if the ray reached ``end_pos`` without leaving the nav-mesh.
+    pub hit: Option<RaycastWallHit>,
+    /// Polygons visited by the ray, in order.
+    pub polygons: Vec<PolyRef>,
+}
+impl RaycastResult {
+    /// Returns true if the straight line between the start & end position stays on the nav-mesh.
+    #[inline]
+    pub fn reached_end(&self) -> bool {
+        self.hit.is_none()
+    }
+}
+
+/// Errors returned by [raycast]
+#[derive(Debug)]
+pub enum RaycastError {
+    /// The tile containing the start polygon doesn't exist.
+    MissingStartTile,
+    /// The start polygon doesn't exist or its tile has been rebuilt since the reference was created.
+    InvalidStartPolygon,
+}
+
+/// Casts a "walkability" ray along the surface of the nav-mesh from ``start_pos`` toward ``end_pos``.
+///
+/// The ray is walked across polygon links on the XZ-plane, meaning it follows the surface of the nav-mesh & ignores height. It stops when it either reaches ``end_pos`` or crosses an edge without a link (a wall).
+///
+/// Useful for checking if an agent can walk in a straight line to a position & for shortcutting paths.
+///
+/// * ``nav_mesh`` - Nav-mesh to cast across.
+/// * ``start_polygon`` - Polygon containing ``start_pos``.
+/// * ``start_pos`` - Start position of the ray. This is clamped to ``start_polygon``.
+/// * ``end_pos`` - End position of the ray.
+/// * ``filter`` - Filter deciding which polygons may be traversed. Links into polygons that don't pass the filter are treated as walls. If **``None``** is supplied every polygon may be traversed.
+pub fn raycast(
+    nav_mesh: &NavMeshTiles,
+    start_polygon: PolyRef,
+    start_pos: Vec3,
+    end_pos: Vec3,
+    filter: Option<&dyn QueryFilter>,
+) -> Result<RaycastResult, RaycastError> {
+    let filter = filter.unwrap_or(&PassAllFilter);
+
+    let start_tile = start_polygon.tile();
+    if !nav_mesh.tiles.contains_key(&start_tile) {
+        return Err(RaycastError::MissingStartTile);
+    }
+    let Some((tile, polygon)) = nav_mesh.get_tile_and_polygon(start_polygon) else {
+        return Err(RaycastError::InvalidStartPolygon);
+    };
+    let start_polygon = start_polygon.polygon();
+
+    let start_pos = tile.get_closest_point_in_polygon(polygon, start_pos);
+    let ray_length = start_pos.xz().distance(end_pos.xz());
+
+    let mut polygons = Vec::new();
+    let mut previous = None;
+    let mut current = (start_tile, start_polygon);
+    let mut hit_t = 0.0;
+
+    // Neighbours are only ever reached through links so these should always exist.
+    while let Some(tile) = nav_mesh.tiles.get(&current.0) {
+        let Some(polygon) = tile.polygons.get(current.1 as usize) else {
+            break;
+        };
+
+        let vertices = tile.get_polygon_vertices(polygon);
+        let Some((_, t_max, exit_edge)) =
+            intersect_segment_polygon_2d(start_pos, end_pos, &vertices)
+        else {
+            // Ray doesn't cross the polygon, we can't go any further.
+            return Ok(RaycastResult {
+                hit: Some(RaycastWallHit {
+                    t: hit_t,
+                    distance: hit_t * ray_length,
+                    normal: Vec3::ZERO,
+                }),
+                polygons,
+            });
+        };
+
+        hit_t = f32::max(hit_t, t_max);
+        polygons.push(nav_mesh.poly_ref_unchecked(current.0, current.1));
+
+        let Some(exit_edge) = exit_edge else {
+            // The ray ends within this polygon.
+            return Ok(RaycastResult {
+                hit: None,
+                polygons,
+            });
+        };
+
+        let exit_point = start_pos.lerp(end_pos, t_max);
+        let next = polygon
+            .links
+            .iter()
+            .filter(|link| link.edge() == exit_edge)
+            .filter(|link| {
+                let (neighbour_tile, neighbour_polygon) = link.neighbour(current.0);
+
+                Some((neighbour_tile, neighbour_polygon)) != previous
+                    && nav_mesh.tiles.get(&neighbour_tile).is_some_and(|tile| {
+                        tile.polygon_passes_filter(neighbour_tile, neighbour_polygon, filter)
+                    })
+            })
+            .find(|link| match link {
+                Link::Internal { .. } => true,
+                Link::External { .. } => {
+                    // External links may only cover part of the edge. Check that we exit within it.
+                    let (portal_a, portal_b) = tile.get_link_portal(polygon, link);
+                    let (distance, _) =
+                        distance_point_to_segment_2d(exit_point, portal_a, portal_b);
+
+                    distance < PORTAL_EPSILON
+                }
+            })
+            .map(|link| link.neighbour(current.0));
+
+        let Some(next) = next else {
+            // No link across this edge, we hit a wall.
+            let (edge_a, edge_b) = tile.get_polygon_edge(polygon, exit_edge);
+            let edge = edge_b - edge_a;
+            let mut normal = Vec3::new(edge.z, 0.0, -edge.x).normalize_or_zero();
+            if normal.dot(end_pos - start_pos) > 0.0 {
+                normal = -normal;
+            }
+
+            return Ok(RaycastResult {
+                hit: Some(RaycastWallHit {
+                    t: t_max,
+                    distance: t_max * ray_length,
+                    normal,
+                }),
+                polygons,
+            });
+        };
+
+        previous = Some(current);
+        current = next;
+    }
+
+    Ok(RaycastResult {
+        hit: Some(RaycastWallHit {
+            t: hit_t,
+            distance: hit_t * ray_length,
+            normal: Vec3::ZERO,
+        }),
+        polygons,
+    })
+}
+
+/// Result of [move_along_surface].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveAlongSurfaceResult {
+    /// The position reached. Either ``end_pos`` or the closest position to it along the walls that were hit, with its height on the nav-mesh.
+    pub position: Vec3,
+    /// Polygons visited from the start polygon to the polygon containing ``position``, in order.
+    pub polygons: Vec<PolyRef>,
+}
+
+/// Errors returned by [move_along_surface]
+#[derive(Debug)]
+pub enum MoveAlongSurfaceError {
+    /// The tile containing the start polygon doesn't exist.
+    MissingStartTile,
+    /// The start polygon doesn't exist or its tile has been rebuilt since the reference was created.
+    InvalidStartPolygon,
+}
+
+/// Moves from ``start_pos`` toward ``end_pos`` while staying on the nav-mesh, sliding along any walls in the way.
+///
+/// Only polygons in a small area around the movement are searched, making this cheap enough to constrain agent movement every frame. It is not meant for long distances, use [find_path] for those.
+///
+/// * ``nav_mesh`` - Nav-mesh to move across.
+/// * ``start_polygon`` - Polygon containing ``start_pos``.
+/// * ``start_pos`` - Position to move from.
+/// * ``end_pos`` - Position to move toward.
+/// * ``filter`` - Filter deciding which polygons may be traversed. Links into polygons that don't pass the filter are treated as walls. If **``None``** is supplied every polygon may be traversed.
+pub fn move_along_surface(
+    nav_mesh: &NavMeshTiles,
+    start_polygon: PolyRef,
+    start_pos: Vec3,
+    end_pos: Vec3,
+    filter: Option<&dyn QueryFilter>,
+) -> Result<MoveAlongSurfaceResult, MoveAlongSurfaceError> {
+    let filter = filter.unwrap_or(&PassAllFilter);
+
+    let start_tile = start_polygon.tile();
+    if !nav_mesh.tiles.contains_key(&start_tile) {
+        return Err(MoveAlongSurfaceError::MissingStartTile);
+    }
+    if !nav_mesh.is_valid_poly_ref(start_polygon) {
+        return Err(MoveAlongSurfaceError::InvalidStartPolygon);
+    }
+    let start_polygon = start_polygon.polygon();
+
+    // Only search polygons touching the circle containing the movement.
+    let search_pos = start_pos.lerp(end_pos, 0.5);
+    let search_radius_squared = (start_pos.xz().distance(end_pos.xz()) * 0.5 + 0.001).powi(2);
+
+    // Tile coordinate, polygon index & index of the parent node.
+    let mut nodes = vec![(start_tile, start_polygon, None)];
+    let mut visited = HashSet::new();
+    visited.insert((start_tile, start_polygon));
+
+    let mut best_node = 0;
+    let mut best_pos = start_pos;
+    let mut best_distance = f32::INFINITY;
+
+    let mut i = 0;
+    while i < nodes.len() {
+        let (tile_coord, polygon_index, _) = nodes[i];
+        let tile = &nav_mesh.tiles[&tile_coord];
+        let polygon = &tile.polygons[polygon_index as usize];
+
+        if in_polygon(&tile.get_polygon_vertices(polygon), end_pos) {
+            best_node = i;
+            best_pos = end_pos;
+            break;
+        }
+
+        for edge in 0..VERTICES_IN_TRIANGLE as u8 {
+            let mut is_wall = true;
+
+            for link in polygon.links.iter().filter(|link| link.edge() == edge) {
+                let neighbour = link.neighbour(tile_coord);
+                if !nav_mesh.tiles.get(&neighbour.0).is_some_and(|tile| {
+                    tile.polygon_passes_filter(neighbour.0, neighbour.1, filter)
+                }) {
+                    continue;
+                }
+                is_wall = false;
+
+                if visited.contains(&neighbour) {
+                    continue;
+                }
+
+                let (portal_a, portal_b) = tile.get_link_portal(polygon, link);
+                let (distance, _) = distance_point_to_segment_2d(search_pos, portal_a, portal_b);
+                if distance > search_radius_squared {
+                    continue;
+                }
+
+                visited.insert(neighbour);
+                nodes.push((neighbour.0, neighbour.1, Some(i)));
+            }
+
+            if is_wall {
+                // Slide along the wall.
+                let (a, b) = tile.get_polygon_edge(polygon, edge);
+                let (distance, t) = distance_point_to_segment_2d(end_pos, a, b);
+                if distance < best_distance {
+                    best_distance = distance;
+                    best_pos = a.lerp(b, t);
+                    best_node = i;
+                }
+            }
+        }
+
+        i += 1;
+    }
+
+    let (tile_coord, polygon, _) = nodes[best_node];
+    let tile = &nav_mesh.tiles[&tile_coord];
+    let position = tile.get_closest_point_in_polygon(&tile.polygons[polygon as usize], best_pos);
+
+    let mut polygons = Vec::new();
+    let mut current = Some(best_node);
+    while let Some(node) = current {
+        let (tile_coord, polygon, parent) = nodes[node];
+        polygons.push(nav_mesh.poly_ref_unchecked(tile_coord, polygon));
+        current = parent;
+    }
+    polygons.reverse();
+
+    Ok(MoveAlongSurfaceResult { position, polygons })
+}
+
+/// The closest wall found by [find_distance_to_wall].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DistanceToWall {
+    /// Distance on the XZ-plane from the search position to the wall.
+    pub distance: f32,
+    /// Closest point on the wall.
+    pub position: Vec3,
+    /// Normal of the wall on the XZ-plane, pointing toward the search position. Zero if the search position is on the wall.
+    pub normal: Vec3,
+    /// Polygon the wall is an edge of.
+    pub polygon: PolyRef,
+    /// Index of the wall's edge on ``polygon``. See [crate::tiles::NavMeshTile::get_polygon_edge].
+    pub edge: u8,
+    /// End points of the wall's edge.
+    pub segment: (Vec3, Vec3),
+}
+
+/// Errors returned by [find_distance_to_wall]
+#[derive(Debug)]
+pub enum FindDistanceToWallError {
+    /// The tile containing the start polygon doesn't exist.
+    MissingStartTile,
+    /// The start polygon doesn't exist or its tile has been rebuilt since the reference was created.
+    InvalidStartPolygon,
+}
+
+/// Finds the closest wall to ``position`` within ``max_radius``. Returns **``None``** if there is no wall within the radius.
+///
+/// A wall is any polygon edge that can't be crossed, either because there is no neighbouring polygon or because the neighbour doesn't pass the filter.
+///
+/// * ``nav_mesh`` - Nav-mesh to search.
+/// * ``start_polygon`` - Polygon containing ``position``.
+/// * ``position`` - Position to search from.
+/// * ``max_radius`` - Maximum distance on the XZ-plane to search for walls.
+/// * ``filter`` - Filter deciding which polygons may be traversed. Links into polygons that don't pass the filter are treated as walls. If **``None``** is supplied every polygon may be traversed.
+pub fn find_distance_to_wall(
+    nav_mesh: &NavMeshTiles,
+    start_polygon: PolyRef,
+    position: Vec3,
+    max_radius: f32,
+    filter: Option<&dyn QueryFilter>,
+) -> Result<Option<DistanceToWall>, FindDistanceToWallError> {
+    let filter = filter.unwrap_or(&PassAllFilter);
+
+    let start_tile = start_polygon.tile();
+    if !nav_mesh.tiles.contains_key(&start_tile) {
+        return Err(FindDistanceToWallError::MissingStartTile);
+    }
+    if !nav_mesh.is_valid_poly_ref(start_polygon) {
+        return Err(FindDistanceToWallError::InvalidStartPolygon);
+    }
+    let start_polygon = start_polygon.polygon();
+
+    // Shrinks as closer walls are found.
+    let mut radius_squared = max_radius * max_radius;
+    let mut hit = None;
+
+    let mut visited = HashSet::new();
+    visited.insert((start_tile, start_polygon));
+    let mut stack = vec![(start_tile, start_polygon)];
+
+    while let Some((tile_coord, polygon_index)) = stack.pop() {
+        let tile = &nav_mesh.tiles[&tile_coord];
+        let polygon = &tile.polygons[polygon_index as usize];
+
+        for edge in 0..VERTICES_IN_TRIANGLE as u8 {
+            let mut is_wall = true;
+
+            for link in polygon.links.iter().filter(|link| link.edge() == edge) {
+                let neighbour = link.neighbour(tile_coord);
+                if !nav_mesh.tiles.get(&neighbour.0).is_some_and(|tile| {
+                    tile.polygon_passes_filter(neighbour.0, neighbour.1, filter)
+                }) {
+                    continue;
+                }
+                is_wall = false;
+
+                if visited.contains(&neighbour) {
+                    continue;
+                }
+
+                // Only expand into polygons that could contain a closer wall.
+                let (portal_a, portal_b) = tile.get_link_portal(polygon, link);
+                let (distance, _) = distance_point_to_segment_2d(position, portal_a, portal_b);
+                if distance > radius_squared {
+                    continue;
+                }
+
+                visited.insert(neighbour);
+                stack.push(neighbour);
+            }
+
+            if is_wall {
+                let (a, b) = tile.get_polygon_edge(polygon, edge);
+                let (distance, t) = distance_point_to_segment_2d(position, a, b);
+                if distance <= radius_squared {
+                    radius_squared = distance;
+                    hit = Some((a.lerp(b, t), tile_coord, polygon_index, edge, (a, b)));
+                }
+            }
+        }
+    }
+
+    Ok(
+        hit.map(|(hit_position, tile_coord, polygon_index, edge, segment)| {
+            let delta = position - hit_position;
+
+            DistanceToWall {
+                distance: radius_squared.sqrt(),
+                position: hit_position,
+                normal: Vec3::new(delta.x, 0.0, delta.z).normalize_or_zero(),
+                polygon: nav_mesh.poly_ref_unchecked(tile_coord, polygon_index),
+                edge,
+                segment,
+            }
+        }),
+    )
+}
+
+/// A polygon reached by [find_polygons_around_circle] or [find_polygons_around_shape].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReachedPolygon {
+    /// The reached polygon.
+    pub polygon: PolyRef,
+    /// The polygon this one was reached from. **``None``** for the start polygon.
+    pub parent: Option<PolyRef>,
+    /// Cost of the cheapest path from the start position to the polygon, as calculated by the filter.
+    pub cost: f32,
+}
+
+/// Errors returned by [find_polygons_around_circle] & [find_polygons_around_shape]
+#[derive(Debug)]
+pub enum FindPolygonsAroundError {
+    /// The tile containing the start polygon doesn't exist.
+    MissingStartTile,
+    /// The start polygon doesn't exist or its tile has been rebuilt since the reference was created.
+    InvalidStartPolygon,
+}
+
+/// Finds the polygons reachable from ``start_polygon`` that touch a circle, using a Dijkstra search across polygon links.
+///
+/// Returns every reached polygon along with the polygon it was reached from & the cost to reach it. The first entry is always the start polygon. Following the parents gives the cheapest path back to the start polygon.
+///
+/// * ``nav_mesh`` - Nav-mesh to search.
+/// * ``start_polygon`` - Polygon containing ``center``.
+/// * ``center`` - Center of the circle.
+/// * ``radius`` - Radius of the circle on the XZ-plane.
+/// * ``filter`` - Filter deciding which polygons may be traversed & at what cost. If **``None``** is supplied every polygon may be traversed & the cost is the distance travelled.
+pub fn find_polygons_around_circle(
+    nav_mesh: &NavMeshTiles,
+    start_polygon: PolyRef,
+    center: Vec3,
+    radius: f32,
+    filter: Option<&dyn QueryFilter>,
+) -> Result<Vec<ReachedPolygon>, FindPolygonsAroundError> {
+    let radius_squared = radius * radius;
+
+    find_polygons_around(
+        nav_mesh,
+        start_polygon,
+        center,
+        filter,
+        |portal_a, portal_b| {
+            let (distance, _) = distance_point_to_segment_2d(center, portal_a, portal_b);
+
+            distance <= radius_squared
+        },
+    )
+}
+
+/// Finds the polygons reachable from ``start_polygon`` that touch a convex shape, using a Dijkstra search across polygon links.
+///
+/// Returns every reached polygon along with the polygon it was reached from & the cost to reach it. The first entry is always the start polygon. Following the parents gives the cheapest path back to the start polygon.
+///
+/// * ``nav_mesh`` - Nav-mesh to search.
+/// * ``start_polygon`` - Polygon containing the center of ``shape``.
+/// * ``shape`` - Vertices of a convex polygon on the XZ-plane. The search starts from the center of the vertices.
+/// * ``filter`` - Filter deciding which polygons may be traversed & at what cost. If **``None``** is supplied every polygon may be traversed & the cost is the distance travelled.
+pub fn find_polygons_around_shape(
+    nav_mesh: &NavMeshTiles,
+    start_polygon: PolyRef,
+    shape: &[Vec3],
+    filter: Option<&dyn QueryFilter>,
+) -> Result<Vec<ReachedPolygon>, FindPolygonsAroundError> {
+    let center = shape.iter().copied().sum::<Vec3>() / shape.len().max(1) as f32;
+
+    find_polygons_around(
+        nav_mesh,
+        start_polygon,
+        center,
+        filter,
+        |portal_a, portal_b| {
+            shape.len() >= 3 && intersect_segment_polygon_2d(portal_a, portal_b, shape).is_some()
+        },
+    )
+}
+
+/// Dijkstra search from ``start_polygon`` only crossing portals ``should_expand`` returns true for.
+fn find_polygons_around(
+    nav_mesh: &NavMeshTiles,
+    start_polygon: PolyRef,
+    start_pos: Vec3,
+    filter: Option<&dyn QueryFilter>,
+    should_expand: impl Fn(Vec3, Vec3) -> bool,
+) -> Result<Vec<ReachedPolygon>, FindPolygonsAroundError> {
+    let filter = filter.unwrap_or(&PassAllFilter);
+
+    let start_tile = start_polygon.tile();
+    if !nav_mesh.tiles.contains_key(&start_tile) {
+        return Err(FindPolygonsAroundError::MissingStartTile);
+    }
+    if !nav_mesh.is_valid_poly_ref(start_polygon) {
+        return Err(FindPolygonsAroundError::InvalidStartPolygon);
+    }
+    let start_polygon = start_polygon.polygon();
+
+    let mut reached = vec![ReachedPolygon {
+        polygon: nav_mesh.poly_ref_unchecked(start_tile, start_polygon),
+        parent: None,
+        cost: 0.0,
+    }];
+    // Position each polygon was entered at & whether it has been expanded. Indexed the same as ``reached``.
+    let mut positions = vec![start_pos];
+    let mut closed = vec![false];
+    let mut lookup = HashMap::new();
+    lookup.insert((start_tile, start_polygon), 0);
+
+    let mut open_list = BinaryHeap::new();
+    open_list.push(OpenListEntry {
+        total_cost: 0.0,
+        node: 0,
+    });
+
+    while let Some(OpenListEntry { total_cost, node }) = open_list.pop() {
+        // Skip stale entries.
+        if closed[node] || total_cost > reached[node].cost {
+            continue;
+        }
+        closed[node] = true;
+
+        let ReachedPolygon {
+            polygon: current,
+            parent,
+            cost,
+        } = reached[node];
+        let (tile_coord, polygon_index) = (current.tile(), current.polygon());
+        let tile = &nav_mesh.tiles[&tile_coord];
+        let polygon = &tile.polygons[polygon_index as usize];
+        let area = tile.areas[polygon_index as usize];
+
+        for link in polygon.links.iter() {
+            let neighbour = link.neighbour(tile_coord);
+            if parent.is_some_and(|parent| (parent.tile(), parent.polygon()) == neighbour) {
+                continue;
+            }
+
+            if !nav_mesh
+                .tiles
+                .get(&neighbour.0)
+                .is_some_and(|tile| tile.polygon_passes_filter(neighbour.0, neighbour.1, filter))
+            {
+                continue;
+            }
+
+            let (portal_a, portal_b) = tile.get_link_portal(polygon, link);
+            if !should_expand(portal_a, portal_b) {
+                continue;
+            }
+
+            let position = portal_a.lerp(portal_b, 0.5);
+            let neighbour_cost = cost + filter.cost(positions[node], position, area, Some(link));
+            if !neighbour_cost.is_finite() {
+                continue;
+            }
+
+            let neighbour_node = match lookup.entry(neighbour) {
+                Entry::Occupied(entry) => {
+                    let neighbour_node = *entry.get();
+                    if closed[neighbour_node] || neighbour_cost >= reached[neighbour_node].cost {
+                        continue;
+                    }
+
+                    reached[neighbour_node].parent = Some(current);
+                    reached[neighbour_node].cost = neighbour_cost;
+                    positions[neighbour_node] = position;
+
+                    neighbour_node
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(reached.len());
+
+                    reached.push(ReachedPolygon {
+                        polygon: nav_mesh.poly_ref_unchecked(neighbour.0, neighbour.1),
+                        parent: Some(current),
+                        cost: neighbour_cost,
+                    });
+                    positions.push(position);
+                    closed.push(false);
+
+                    reached.len() - 1
+                }
+            };
+
+            open_list.push(OpenListEntry {
+                total_cost: neighbour_cost,
+                node: neighbour_node,
+            });
+        }
+    }
+
+    Ok(reached)
+}
+
+/// Returns a random point on the nav-mesh as a tuple of (polygon, position).
+///
+/// Polygons are picked weighted by their area, giving an even distribution of points across the whole nav-mesh. Returns **``None``** if no polygon passes the filter.
+///
+/// * ``nav_mesh`` - Nav-mesh to pick a point on.
+/// * ``filter`` - Filter deciding which polygons may be picked. If **``None``** is supplied every polygon may be picked.
+/// * ``random`` - Function returning a random number in the range ``[0, 1)``. Called multiple times.
+pub fn find_random_point(
+    nav_mesh: &NavMeshTiles,
+    filter: Option<&dyn QueryFilter>,
+    mut random: impl FnMut() -> f32,
+) -> Option<(PolyRef, Vec3)> {
+    let filter = filter.unwrap_or(&PassAllFilter);
+
+    let mut selected = None;
+    let mut area_sum = 0.0;
+    for (tile_coord, tile) in nav_mesh.tiles.iter() {
+        for (polygon_index, polygon) in tile.polygons.iter().enumerate() {
+            if !tile.polygon_passes_filter(*tile_coord, polygon_index as u16, filter) {
+                continue;
+            }
+
+            let vertices = tile.get_polygon_vertices(polygon);
+            let polygon_area = triangle_area_2d(vertices[0], vertices[1], vertices[2]).abs();
+
+            // Reservoir sampling weighted by area.
+            area_sum += polygon_area;
+            if random() * area_sum <= polygon_area {
+                selected = Some((*tile_coord, polygon_index as u16, vertices));
+            }
+        }
+    }
+
+    selected.map(|(tile_coord, polygon, vertices)| {
+        (
+            nav_mesh.poly_ref_unchecked(tile_coord, polygon),
+            random_point_in_triangle(&vertices, random(), random()),
+        )
+    })
+}
+
+/// Errors returned by [find_random_point_around_circle]
+#[derive(Debug)]
+pub enum FindRandomPointAroundCircleError {
+    /// The tile containing the start polygon doesn't exist.
+    MissingStartTile,
+    /// The start polygon doesn't exist, its tile has been rebuilt since the reference was created or it doesn't pass the filter.
+    InvalidStartPolygon,
+}
+
+/// Returns a random point on a polygon reachable from ``start_polygon`` within ``radius`` of ``start_pos`` as a tuple of (polygon, position).
+///
+/// Polygons are picked weighted by their area. Any polygon touching the circle may be picked, meaning the point itself may be slightly outside of ``radius``.
+///
+/// * ``nav_mesh`` - Nav-mesh to pick a point on.
+/// * ``start_polygon`` - Polygon containing ``start_pos``.
+/// * ``start_pos`` - Center of the circle.
+/// * ``radius`` - Radius of the circle on the XZ-plane.
+/// * ``filter`` - Filter deciding which polygons may be traversed & picked. If **``None``** is supplied every polygon may be traversed.
+/// * ``random`` - Function returning a random number in the range ``[0, 1)``. Called multiple times.
+pub fn find_random_point_around_circle(
+    nav_mesh: &NavMeshTiles,
+    start_polygon: PolyRef,
+    start_pos: Vec3,
+    radius: f32,
+    filter: Option<&dyn QueryFilter>,
+    mut random: impl FnMut() -> f32,
+) -> Result<(PolyRef, Vec3), FindRandomPointAroundCircleError> {
+    let filter = filter.unwrap_or(&PassAllFilter);
+
+    let start_tile = start_polygon.tile();
+    if !nav_mesh.tiles.contains_key(&start_tile) {
+        return Err(FindRandomPointAroundCircleError::MissingStartTile);
+    }
+    let Some((tile, polygon)) = nav_mesh.get_tile_and_polygon(start_polygon) else {
+        return Err(FindRandomPointAroundCircleError::InvalidStartPolygon);
+    };
+    let start_polygon = start_polygon.polygon();
+    if !tile.polygon_passes_filter(start_tile, start_polygon, filter) {
+        return Err(FindRandomPointAroundCircleError::InvalidStartPolygon);
     }
+
+    let radius_squared = radius * radius;
+
+    let mut visited = HashSet::new();
+    visited.insert((start_tile, start_polygon));
+    let mut stack = vec![(start_tile, start_polygon)];
+
+    let mut selected = (
+        start_tile,
+        start_polygon,
+        tile.get_polygon_vertices(polygon),
+    );
+    let mut area_sum = 0.0;
+    while let Some((tile_coord, polygon_index)) = stack.pop() {
+        let tile = &nav_mesh.tiles[&tile_coord];
+        let polygon = &tile.polygons[polygon_index as usize];
+
+        let vertices = tile.get_polygon_vertices(polygon);
+        let polygon_area = triangle_area_2d(vertices[0], vertices[1], vertices[2]).abs();
+
+        // Reservoir sampling weighted by area.
+        area_sum += polygon_area;
+        if random() * area_sum <= polygon_area {
+            selected = (tile_coord, polygon_index, vertices);
+        }
+
+        for link in polygon.links.iter() {
+            let (neighbour_tile, neighbour_polygon) = link.neighbour(tile_coord);
+            if visited.contains(&(neighbour_tile, neighbour_polygon)) {
+                continue;
+            }
+
+            // Only expand into polygons touching the circle.
+            let (portal_a, portal_b) = tile.get_link_portal(polygon, link);
+            let (distance, _) = distance_point_to_segment_2d(start_pos, portal_a, portal_b);
+            if distance > radius_squared {
+                continue;
+            }
+
+            if !nav_mesh.tiles.get(&neighbour_tile).is_some_and(|tile| {
+                tile.polygon_passes_filter(neighbour_tile, neighbour_polygon, filter)
+            }) {
+                continue;
+            }
+
+            visited.insert((neighbour_tile, neighbour_polygon));
+            stack.push((neighbour_tile, neighbour_polygon));
+        }
+    }
+
+    let (tile_coord, polygon, vertices) = selected;
+
+    Ok((
+        nav_mesh.poly_ref_unchecked(tile_coord, polygon),
+        random_point_in_triangle(&vertices, random(), random()),
+    ))
+}
+
+/// Returns a uniformly distributed point in the triangle ``vertices`` from two random numbers in the range ``[0, 1)``.
+fn random_point_in_triangle(vertices: &[Vec3; VERTICES_IN_TRIANGLE], a: f32, b: f32) -> Vec3 {
+    // Fold points outside of the triangle back into it.
+    let (a, b) = if a + b > 1.0 {
+        (1.0 - a, 1.0 - b)
+    } else {
+        (a, b)
+    };
+
+    vertices[0] + (vertices[1] - vertices[0]) * a + (vertices[2] - vertices[0]) * b
+}
+
+/// Squared distance on the XZ-plane an exit point may be from a portal and still be considered inside it.
+const PORTAL_EPSILON: f32 = 0.001 * 0.001;
+
+/// Intersects the segment ``start`` -> ``end`` with a convex polygon on the XZ-plane.
+///
+/// Returns the entering & exiting fraction along the segment and the edge the segment exits through. The edge is **``None``** if the segment ends inside the polygon.
+fn intersect_segment_polygon_2d(
+    start: Vec3,
+    end: Vec3,
+    vertices: &[Vec3],
+) -> Option<(f32, f32, Option<u8>)> {
+    const EPS: f32 = 0.00000001;
+
+    let mut t_min = 0.0;
+    let mut t_max = 1.0;
+    let mut exit_edge = None;
+
+    let direction = end - start;
+
+    // Sign of the polygon's winding, used to make the edge tests winding independent.
+    let winding = triangle_area_2d(vertices[0], vertices[1], vertices[2]).signum();
+
+    for i in 0..vertices.len() {
+        let a = vertices[i];
+        let b = vertices[(i + 1) % vertices.len()];
+
+        let n = triangle_area_2d(a, b, start) * winding;
+        let d = cross_2d(b - a, direction) * winding;
+
+        if d.abs() < EPS {
+            // Segment is parallel to this edge.
+            if n < 0.0 {
+                return None;
+            }
+            continue;
+        }
+
+        let t = -n / d;
+        if d > 0.0 {
+            // Segment is entering across this edge.
+            if t > t_min {
+                t_min = t;
+                if t_min > t_max {
+                    return None;
+                }
+            }
+        } else if t < t_max {
+            // Segment is leaving across this edge.
+            t_max = t;
+            exit_edge = Some(i as u8);
+            if t_max < t_min {
+                return None;
+            }
+        }
+    }
+
+    Some((t_min, t_max, exit_edge))
+}
+
+/// Intersects the segments ``a_start`` -> ``a_end`` & ``b_start`` -> ``b_end`` on the XZ-plane.
+///
+/// Returns the fraction along each segment of the intersection, **``None``** if the segments are parallel or don't intersect.
+fn intersect_segments_2d(
+    a_start: Vec3,
+    a_end: Vec3,
+    b_start: Vec3,
+    b_end: Vec3,
+) -> Option<(f32, f32)> {
+    const EPS: f32 = 0.000001;
+
+    let u = a_end - a_start;
+    let v = b_end - b_start;
+    let w = a_start - b_start;
+
+    let d = cross_2d(u, v);
+    if d.abs() < EPS {
+        return None;
+    }
+
+    let s = cross_2d(v, w) / d;
+    let t = cross_2d(u, w) / d;
+    if !(0.0..=1.0).contains(&s) || !(-EPS..=1.0 + EPS).contains(&t) {
+        return None;
+    }
+
+    Some((s, t.clamp(0.0, 1.0)))
+}
+
+fn cross_2d(a: Vec3, b: Vec3) -> f32 {
+    a.z * b.x - a.x * b.z
 }
 
 fn triangle_area_2d(a: Vec3, b: Vec3, c: Vec3) -> f32 {
@@ -184,6 +184,7 @@ fn expand_regions(
             let mut distance = u16::MAX;
             let span = &tile.cells[entry.cell_index as usize].spans[entry.span_index as usize];
             let area = tile.areas[span.tile_index];
+            let flags = tile.flags[span.tile_index];
 
             for dir in 0..4 {
                 let Some(span_index) = span.neighbours[dir] else {
@@ -194,7 +195,7 @@ fn expand_regions(
                     [get_neighbour_index(tile_side, entry.cell_index as usize, dir)]
                 .spans[span_index as usize];
                 let other_area = tile.areas[other_span.tile_index];
-                if other_area != area {
+                if other_area != area || tile.flags[other_span.tile_index] != flags {
                     continue;
                 }
 
@@ -272,6 +273,7 @@ fn expand_regions_until_end(
             let mut distance = u16::MAX;
             let span = &tile.cells[entry.cell_index as usize].spans[entry.span_index as usize];
             let area = tile.areas[span.tile_index];
+            let flags = tile.flags[span.tile_index];
 
             for dir in 0..4 {
                 let Some(index) = span.neighbours[dir] else {
@@ -282,7 +284,7 @@ fn expand_regions_until_end(
                     [get_neighbour_index(tile_side, entry.cell_index as usize, dir)]
                 .spans[index as usize];
                 let other_area = tile.areas[other_span.tile_index];
-                if other_area != area {
+                if other_area != area || tile.flags[other_span.tile_index] != flags {
                     continue;
                 }
 
@@ -326,6 +328,7 @@ struct Region {
     floors: Vec<u16>,
     connections: Vec<u16>,
     area: Option<Area>,
+    flags: u16,
 }
 
 fn merge_regions(
@@ -346,6 +349,7 @@ fn merge_regions(
             floors: Vec::with_capacity(4),
             connections: Vec::with_capacity(4),
             area: None,
+            flags: 0,
         });
     }
 
@@ -380,6 +384,7 @@ fn merge_regions(
             }
 
             region.area = tile.areas[span.tile_index];
+            region.flags = tile.flags[span.tile_index];
 
             let dir = {
                 let mut dir = None;
@@ -644,7 +649,7 @@ fn remove_adjacent_connection_duplicates(region: &mut Region) {
 }
 
 fn can_merge_with_region(a: &Region, b: &Region) -> bool {
-    if a.area != b.area {
+    if a.area != b.area || a.flags != b.flags {
         return false;
     }
 
@@ -775,6 +780,7 @@ fn flood_region(
 
     let span = &tile.cells[entry.cell_index as usize].spans[entry.span_index as usize];
     let area = tile.areas[span.tile_index];
+    let flags = tile.flags[span.tile_index];
 
     regions[entry.index as usize] = region_id;
     distances[entry.index as usize] = 0;
@@ -796,7 +802,7 @@ fn flood_region(
             let other_region = regions[other_span.tile_index];
             let other_area = tile.areas[other_span.tile_index];
 
-            if other_area != area {
+            if other_area != area || tile.flags[other_span.tile_index] != flags {
                 continue;
             }
 
@@ -813,7 +819,7 @@ fn flood_region(
                 let other_region = regions[other_span.tile_index];
                 let other_area = tile.areas[other_span.tile_index];
 
-                if other_area != area {
+                if other_area != area || tile.flags[other_span.tile_index] != flags {
                     continue;
                 }
 
@@ -840,7 +846,7 @@ fn flood_region(
             let other_cell_index = get_neighbour_index(tile_side, entry.cell_index as usize, dir);
             let other_span = &tile.cells[other_cell_index].spans[span_index as usize];
             let other_area = tile.areas[other_span.tile_index];
-            if other_area != area {
+            if other_area != area || tile.flags[other_span.tile_index] != flags {
                 continue;
             }
 
@@ -0,0 +1,353 @@
+//! Module for running pathfinding asynchronously through tickets.
+//!
+//! Submit a [PathfindingRequest] to the [PathfindingQueue] resource & keep the returned [PathTicket].
+//! The plugin runs queued requests on the [AsyncComputeTaskPool], highest priority first & never more than [PathfindingQueue::max_tasks] at a time.
+//! Once finished the result is sent as a [PathfindingFinished] event & kept in the queue until it's taken with [PathfindingQueue::take_result] or the ticket is cancelled.
+
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    num::NonZeroU16,
+    sync::{Arc, Mutex, RwLock},
+};
+
+use bevy::{
+    platform::collections::HashMap,
+    prelude::*,
+    tasks::{futures_lite::future, AsyncComputeTaskPool, Task},
+};
+
+use crate::{
+    query::{
+        perform_string_pulling_on_path, FindPathError, FindPolygonPathError, NavMeshQuery, Path,
+        QueryFilter,
+    },
+    tiles::{NavMeshTiles, PolyRef},
+    NavMesh, NavMeshSettings,
+};
+
+/// Handle to a request submitted to the [PathfindingQueue].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PathTicket(u64);
+
+/// A pathfinding request. Runs [crate::query::find_path] when its turn comes.
+#[derive(Clone)]
+pub struct PathfindingRequest {
+    /// Starting position for the path.
+    pub start_pos: Vec3,
+    /// Destination position for the path.
+    pub end_pos: Vec3,
+    /// Half extents of the box to search for a start & end polygon in. See [crate::query::find_polygon_path].
+    pub position_search_extents: Option<Vec3>,
+    /// Filter deciding which polygons may be traversed & at what cost. See [crate::query::find_polygon_path].
+    pub filter: Option<Arc<dyn QueryFilter + Send + Sync>>,
+    /// Requests with a higher priority are started first. Requests with the same priority are started in the order they were submitted.
+    pub priority: i32,
+}
+
+impl PathfindingRequest {
+    /// Creates a request for a path from ``start_pos`` to ``end_pos`` with a priority of ``0``.
+    pub fn new(start_pos: Vec3, end_pos: Vec3) -> Self {
+        Self {
+            start_pos,
+            end_pos,
+            position_search_extents: None,
+            filter: None,
+            priority: 0,
+        }
+    }
+
+    /// Setter for [PathfindingRequest::position_search_extents]
+    pub fn with_position_search_extents(mut self, position_search_extents: Vec3) -> Self {
+        self.position_search_extents = Some(position_search_extents);
+        self
+    }
+
+    /// Setter for [PathfindingRequest::filter]
+    pub fn with_filter(mut self, filter: impl QueryFilter + Send + Sync + 'static) -> Self {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Setter for [PathfindingRequest::priority]
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Where a [PathTicket] is in the [PathfindingQueue].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathTicketStatus {
+    /// Waiting for a free task.
+    Queued,
+    /// Running on the [AsyncComputeTaskPool].
+    Running,
+    /// Finished, the result can be taken with [PathfindingQueue::take_result].
+    Finished,
+}
+
+/// Event sent when a request submitted to the [PathfindingQueue] has finished.
+#[derive(Event, Debug, Clone)]
+pub struct PathfindingFinished {
+    pub ticket: PathTicket,
+    pub result: Result<Path, FindPathError>,
+    /// The polygons the path crosses, in order from start to end. Empty if no path was found.
+    pub polygons: Vec<PolyRef>,
+    /// Highest tile generation of the nav-mesh when the path was found. A tile with a newer generation means the nav-mesh has changed since, see [crate::tiles::NavMeshTiles::tile_generations].
+    pub generation: u64,
+}
+
+/// Queue of pathfinding requests run asynchronously by the plugin. See the [module level documentation](self).
+#[derive(Resource)]
+pub struct PathfindingQueue {
+    max_tasks: Option<NonZeroU16>,
+    next_ticket: u64,
+    queued: BinaryHeap<QueuedRequest>,
+    running: Vec<(PathTicket, PathfindingTask)>,
+    /// Results of the finished requests that haven't been taken or cancelled yet.
+    finished: HashMap<PathTicket, Result<Path, FindPathError>>,
+    /// Queries reused between requests so their allocations are kept. Holds at most one per task that has run at the same time.
+    query_pool: Arc<Mutex<Vec<NavMeshQuery>>>,
+}
+
+impl Default for PathfindingQueue {
+    fn default() -> Self {
+        Self::new(NonZeroU16::new(4))
+    }
+}
+
+impl PathfindingQueue {
+    /// Creates a new queue.
+    ///
+    /// * ``max_tasks`` - Maximum number of requests running at the same time. A value of ``None`` will result in no limit.
+    pub fn new(max_tasks: Option<NonZeroU16>) -> Self {
+        Self {
+            max_tasks,
+            next_ticket: 0,
+            queued: BinaryHeap::new(),
+            running: Vec::new(),
+            finished: HashMap::new(),
+            query_pool: Arc::default(),
+        }
+    }
+
+    /// Returns the maximum number of requests running at the same time.
+    pub fn max_tasks(&self) -> Option<NonZeroU16> {
+        self.max_tasks
+    }
+
+    /// Setter for the maximum number of requests running at the same time. A value of ``None`` will result in no limit.
+    ///
+    /// Lowering the limit doesn't stop requests that are already running.
+    pub fn set_max_tasks(&mut self, max_tasks: Option<NonZeroU16>) {
+        self.max_tasks = max_tasks;
+    }
+
+    /// Queues ``request`` & returns the ticket identifying it.
+    pub fn submit(&mut self, request: PathfindingRequest) -> PathTicket {
+        let ticket = PathTicket(self.next_ticket);
+        self.next_ticket += 1;
+
+        self.queued.push(QueuedRequest { ticket, request });
+
+        ticket
+    }
+
+    /// Cancels the request for ``ticket``, dropping its result if it has already finished. Returns true if the ticket was known.
+    pub fn cancel(&mut self, ticket: PathTicket) -> bool {
+        let queued_count = self.queued.len();
+        self.queued.retain(|queued| queued.ticket != ticket);
+        if self.queued.len() != queued_count {
+            return true;
+        }
+
+        if let Some(index) = self
+            .running
+            .iter()
+            .position(|(running, _)| *running == ticket)
+        {
+            // Dropping the task cancels it.
+            drop(self.running.swap_remove(index));
+            return true;
+        }
+
+        self.finished.remove(&ticket).is_some()
+    }
+
+    /// Returns the status of ``ticket``. **``None``** if it's unknown, cancelled or its result has been taken.
+    pub fn get_status(&self, ticket: PathTicket) -> Option<PathTicketStatus> {
+        if self.finished.contains_key(&ticket) {
+            Some(PathTicketStatus::Finished)
+        } else if self.running.iter().any(|(running, _)| *running == ticket) {
+            Some(PathTicketStatus::Running)
+        } else if self.queued.iter().any(|queued| queued.ticket == ticket) {
+            Some(PathTicketStatus::Queued)
+        } else {
+            None
+        }
+    }
+
+    /// Takes the result of ``ticket`` if it has finished.
+    ///
+    /// Results are kept until they're taken or the ticket is cancelled, so take or cancel every ticket you submit, even if you read the [PathfindingFinished] events instead.
+    pub fn take_result(&mut self, ticket: PathTicket) -> Option<Result<Path, FindPathError>> {
+        self.finished.remove(&ticket)
+    }
+
+    /// Returns the number of requests waiting for a free task.
+    pub fn queued_len(&self) -> usize {
+        self.queued.len()
+    }
+
+    /// Returns the number of requests currently running.
+    pub fn running_len(&self) -> usize {
+        self.running.len()
+    }
+}
+
+/// Task returning the result, the polygons crossed & the nav-mesh generation it was found at.
+type PathfindingTask = Task<(Result<Path, FindPathError>, Vec<PolyRef>, u64)>;
+
+struct QueuedRequest {
+    ticket: PathTicket,
+    request: PathfindingRequest,
+}
+impl PartialEq for QueuedRequest {
+    fn eq(&self, other: &Self) -> bool {
+        self.ticket == other.ticket
+    }
+}
+impl Eq for QueuedRequest {}
+impl PartialOrd for QueuedRequest {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for QueuedRequest {
+    // Highest priority first, then oldest ticket first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.request
+            .priority
+            .cmp(&other.request.priority)
+            .then_with(|| other.ticket.cmp(&self.ticket))
+    }
+}
+
+pub(crate) fn process_pathfinding_queue_system(
+    mut pathfinding_queue: ResMut<PathfindingQueue>,
+    mut event: EventWriter<PathfindingFinished>,
+    nav_mesh_settings: Res<NavMeshSettings>,
+    nav_mesh: Res<NavMesh>,
+) {
+    let pathfinding_queue = &mut *pathfinding_queue;
+
+    let mut i = 0;
+    while i < pathfinding_queue.running.len() {
+        let (ticket, task) = &mut pathfinding_queue.running[i];
+        let Some((result, polygons, generation)) = future::block_on(future::poll_once(task)) else {
+            i += 1;
+            continue;
+        };
+
+        event.write(PathfindingFinished {
+            ticket: *ticket,
+            result: result.clone(),
+            polygons,
+            generation,
+        });
+        pathfinding_queue.finished.insert(*ticket, result);
+        drop(pathfinding_queue.running.swap_remove(i));
+    }
+
+    let thread_pool = AsyncComputeTaskPool::get();
+
+    let max_tasks = pathfinding_queue
+        .max_tasks
+        .map_or(usize::MAX, |max_tasks| max_tasks.get() as usize);
+    while pathfinding_queue.running.len() < max_tasks {
+        let Some(QueuedRequest { ticket, request }) = pathfinding_queue.queued.pop() else {
+            break;
+        };
+
+        let task = thread_pool.spawn(find_path_task(
+            nav_mesh.get(),
+            nav_mesh_settings.clone(),
+            pathfinding_queue.query_pool.clone(),
+            request,
+        ));
+
+        pathfinding_queue.running.push((ticket, task));
+    }
+}
+
+async fn find_path_task(
+    nav_mesh: Arc<RwLock<NavMeshTiles>>,
+    nav_mesh_settings: NavMeshSettings,
+    query_pool: Arc<Mutex<Vec<NavMeshQuery>>>,
+    request: PathfindingRequest,
+) -> (Result<Path, FindPathError>, Vec<PolyRef>, u64) {
+    let Ok(nav_mesh) = nav_mesh.read() else {
+        return (
+            Err(FindPathError::PolygonPath(
+                FindPolygonPathError::NavMeshUnavailable,
+            )),
+            Vec::new(),
+            0,
+        );
+    };
+
+    let generation = nav_mesh
+        .tile_generations
+        .values()
+        .copied()
+        .max()
+        .unwrap_or(0);
+
+    let mut query = query_pool
+        .lock()
+        .ok()
+        .and_then(|mut query_pool| query_pool.pop())
+        .unwrap_or_default();
+
+    // Same as [NavMeshQuery::find_path] but keeping the polygons crossed.
+    let result = query
+        .find_polygon_path(
+            &nav_mesh,
+            &nav_mesh_settings,
+            request.start_pos,
+            request.end_pos,
+            request.position_search_extents,
+            request
+                .filter
+                .as_deref()
+                .map(|filter| filter as &dyn QueryFilter),
+        )
+        .map_err(FindPathError::PolygonPath)
+        .and_then(|polygon_path| {
+            let points = perform_string_pulling_on_path(
+                &nav_mesh,
+                request.start_pos,
+                request.end_pos,
+                &polygon_path.polygons,
+            )
+            .map_err(FindPathError::StringPulling)?;
+
+            Ok((
+                Path {
+                    points,
+                    status: polygon_path.status,
+                },
+                polygon_path.polygons,
+            ))
+        });
+
+    if let Ok(mut query_pool) = query_pool.lock() {
+        query_pool.push(query);
+    }
+
+    match result {
+        Ok((path, polygons)) => (Ok(path), polygons, generation),
+        Err(error) => (Err(error), Vec::new(), generation),
+    }
+}
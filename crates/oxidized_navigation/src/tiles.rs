@@ -1,12 +1,14 @@
 use bevy::{
     math::Vec3Swizzles,
-    platform::collections::HashMap,
+    platform::collections::{HashMap, HashSet},
     prelude::{UVec2, Vec2, Vec3},
 };
 use smallvec::SmallVec;
 
 use crate::{
+    hierarchy::AbstractGraph,
     mesher::{EdgeConnection, EdgeConnectionDirection, VERTICES_IN_TRIANGLE},
+    query::QueryFilter,
     Area, NavMeshSettings,
 };
 
@@ -35,6 +37,32 @@ pub enum Link {
     },
 }
 
+impl Link {
+    /// Returns the index of the edge on the owning polygon that this link crosses.
+    #[inline]
+    pub fn edge(&self) -> u8 {
+        match self {
+            Link::Internal { edge, .. } => *edge,
+            Link::External { edge, .. } => *edge,
+        }
+    }
+
+    /// Returns the tile coordinate & polygon index this link leads to when followed from a polygon in ``tile_coord``.
+    #[inline]
+    pub fn neighbour(&self, tile_coord: UVec2) -> (UVec2, u16) {
+        match self {
+            Link::Internal {
+                neighbour_polygon, ..
+            } => (tile_coord, *neighbour_polygon),
+            Link::External {
+                neighbour_polygon,
+                direction,
+                ..
+            } => (direction.offset(tile_coord), *neighbour_polygon),
+        }
+    }
+}
+
 /// A polygon within a nav-mesh tile.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Polygon {
@@ -42,6 +70,21 @@ pub struct Polygon {
     pub links: SmallVec<[Link; VERTICES_IN_TRIANGLE]>, // This becomes a mess memory wise with a ton of different small objects around.
 }
 
+/// A node in a [NavMeshTile]'s bounding volume tree.
+///
+/// Nodes are stored depth-first, so the children of an internal node directly follow it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BvNode {
+    /// Minimum world space corner of the bounds.
+    pub min: Vec3,
+    /// Maximum world space corner of the bounds.
+    pub max: Vec3,
+    /// Polygon index for leaf nodes. **``None``** for internal nodes.
+    pub polygon: Option<u16>,
+    /// Amount of nodes in this node's subtree (including itself). Skipping this many nodes moves past the subtree.
+    pub escape: u32,
+}
+
 /*
 *   Polygons make up a form of graph, linking to other polygons (which could be on another mesh)
 */
@@ -53,12 +96,97 @@ pub struct NavMeshTile {
     pub vertices: Box<[Vec3]>,
     pub polygons: Box<[Polygon]>,
     pub areas: Box<[Area]>,
+    /// Flags of each polygon. See [crate::NavMeshPolygonFlags].
+    pub flags: Box<[u16]>,
     pub edges: Box<[[EdgeConnection; VERTICES_IN_TRIANGLE]]>,
+    /// Bounding volume tree over the polygons. Used to speed up spatial queries.
+    pub bv_tree: Box<[BvNode]>,
+    /// Lowest world space Y of any vertex in the tile.
+    pub min_height: f32,
+    /// Highest world space Y of any vertex in the tile.
+    pub max_height: f32,
 }
 impl NavMeshTile {
+    /// Calls ``callback`` with the index of every polygon whose bounds overlap the box between ``min`` & ``max``.
+    pub fn query_polygons_in_box(&self, min: Vec3, max: Vec3, mut callback: impl FnMut(u16)) {
+        if self.max_height < min.y || self.min_height > max.y {
+            return;
+        }
+
+        let mut i = 0;
+        while i < self.bv_tree.len() {
+            let node = &self.bv_tree[i];
+            let overlaps = node.min.cmple(max).all() && node.max.cmpge(min).all();
+
+            if let (true, Some(polygon)) = (overlaps, node.polygon) {
+                callback(polygon);
+            }
+
+            if overlaps || node.polygon.is_some() {
+                i += 1;
+            } else {
+                i += node.escape as usize;
+            }
+        }
+    }
+
+    /// Returns the world space vertices of ``polygon``.
+    #[inline]
+    pub fn get_polygon_vertices(&self, polygon: &Polygon) -> [Vec3; VERTICES_IN_TRIANGLE] {
+        polygon.indices.map(|index| self.vertices[index as usize])
+    }
+
+    /// Returns the world space end points of ``edge`` on ``polygon``.
+    #[inline]
+    pub fn get_polygon_edge(&self, polygon: &Polygon, edge: u8) -> (Vec3, Vec3) {
+        let indices = &polygon.indices;
+        let a = self.vertices[indices[edge as usize] as usize];
+        let b = self.vertices[indices[(edge as usize + 1) % indices.len()] as usize];
+
+        (a, b)
+    }
+
+    /// Returns the end points of the portal ``link`` forms on ``polygon``'s edge.
+    ///
+    /// For external links this is the part of the edge bound by ``bound_min`` & ``bound_max``.
+    pub fn get_link_portal(&self, polygon: &Polygon, link: &Link) -> (Vec3, Vec3) {
+        match link {
+            Link::Internal { edge, .. } => self.get_polygon_edge(polygon, *edge),
+            Link::External {
+                edge,
+                bound_min,
+                bound_max,
+                ..
+            } => {
+                let (a, b) = self.get_polygon_edge(polygon, *edge);
+
+                const S: f32 = 1.0 / 255.0;
+                let clamped_a = a.lerp(b, *bound_min as f32 * S);
+                let clamped_b = a.lerp(b, *bound_max as f32 * S);
+
+                (clamped_a, clamped_b)
+            }
+        }
+    }
+
+    /// Returns true if ``polygon`` exists in this tile & passes ``filter``.
+    ///
+    /// * ``tile_coord`` - Coordinate of this tile.
+    pub fn polygon_passes_filter(
+        &self,
+        tile_coord: UVec2,
+        polygon: u16,
+        filter: &dyn QueryFilter,
+    ) -> bool {
+        self.areas
+            .get(polygon as usize)
+            .zip(self.flags.get(polygon as usize))
+            .is_some_and(|(area, flags)| filter.pass_filter(tile_coord, polygon, *area, *flags))
+    }
+
     /// Returns the closest point on ``polygon`` to ``position``.
     pub fn get_closest_point_in_polygon(&self, polygon: &Polygon, position: Vec3) -> Vec3 {
-        let vertices: [Vec3; 3] = polygon.indices.map(|index| self.vertices[index as usize]);
+        let vertices = self.get_polygon_vertices(polygon);
 
         if let Some(height) = get_height_in_triangle(&vertices, position) {
             return Vec3::new(position.x, height, position.z);
@@ -68,6 +196,72 @@ impl NavMeshTile {
     }
 }
 
+/// Maximum squared distance on the XZ-plane between a position & its projection for it to count as over the polygon.
+const OVER_POLYGON_EPSILON: f32 = 0.0001 * 0.0001;
+
+/// Result of [NavMeshTiles::project_point].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProjectedPoint {
+    /// The polygon closest to the projected position.
+    pub polygon: PolyRef,
+    /// The closest point on ``polygon``.
+    pub position: Vec3,
+    /// True if the projected position is directly above or below ``polygon``, meaning only its height was changed.
+    pub is_over_polygon: bool,
+}
+
+/// Reference to a polygon in [NavMeshTiles] that can safely be held on to while tiles are regenerated.
+///
+/// Along with the tile coordinate & polygon index it stores the tile's salt when the reference was created. The salt is incremented by [NavMeshTiles::add_tile] & [NavMeshTiles::remove_tile].
+/// Once the tile is rebuilt or removed the salt no longer matches & [NavMeshTiles::is_valid_poly_ref] returns false, instead of the reference silently pointing to an unrelated polygon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PolyRef {
+    tile: UVec2,
+    polygon: u16,
+    salt: u32,
+}
+
+impl PolyRef {
+    /// Creates a reference from its parts. Prefer [NavMeshTiles::get_poly_ref] which uses the tile's current salt.
+    #[inline]
+    pub fn new(tile: UVec2, polygon: u16, salt: u32) -> Self {
+        Self {
+            tile,
+            polygon,
+            salt,
+        }
+    }
+
+    /// Coordinate of the tile containing the polygon.
+    #[inline]
+    pub fn tile(&self) -> UVec2 {
+        self.tile
+    }
+
+    /// Index of the polygon within its tile.
+    #[inline]
+    pub fn polygon(&self) -> u16 {
+        self.polygon
+    }
+
+    /// Salt of the tile when the reference was created.
+    #[inline]
+    pub fn salt(&self) -> u32 {
+        self.salt
+    }
+}
+
+/// Connected components of a tile's polygons, used to track islands. See [NavMeshTiles::island_of].
+#[derive(Debug, Clone, Default, PartialEq)]
+struct TileIslands {
+    /// Component of every polygon, components are only connected through internal links.
+    polygon_components: Vec<u16>,
+    /// Island of every component.
+    component_islands: Vec<u32>,
+    /// Components in neighbouring tiles every component links to as (tile, component).
+    component_links: Vec<Vec<(UVec2, u16)>>,
+}
+
 /// Container for all nav-mesh tiles. Used for pathfinding queries.
 ///
 /// Call [crate::query::find_path] to run pathfinding algorithm.
@@ -75,15 +269,97 @@ impl NavMeshTile {
 pub struct NavMeshTiles {
     pub tiles: HashMap<UVec2, NavMeshTile>,
     pub tile_generations: HashMap<UVec2, u64>,
+    /// Salt of every tile that has been added or removed, incremented each time. See [PolyRef].
+    tile_salts: HashMap<UVec2, u32>,
+    abstract_graph: AbstractGraph,
+    /// Connected components & their islands by tile. See [NavMeshTiles::island_of].
+    islands: HashMap<UVec2, TileIslands>,
+    next_island: u32,
 }
 
 impl NavMeshTiles {
+    /// Returns the [AbstractGraph] used by [crate::query::find_polygon_path_hierarchical].
+    #[inline]
+    pub fn abstract_graph(&self) -> &AbstractGraph {
+        &self.abstract_graph
+    }
+
     /// Returns a [HashMap] containing all tiles in the nav-mesh.
     pub fn get_tiles(&self) -> &HashMap<UVec2, NavMeshTile> {
         &self.tiles
     }
 
-    pub(super) fn add_tile(
+    /// Returns a [PolyRef] to ``polygon`` in the tile at ``tile_coord`` using the tile's current salt. **``None``** if the polygon doesn't exist.
+    pub fn get_poly_ref(&self, tile_coord: UVec2, polygon: u16) -> Option<PolyRef> {
+        let tile = self.tiles.get(&tile_coord)?;
+        if polygon as usize >= tile.polygons.len() {
+            return None;
+        }
+
+        Some(self.poly_ref_unchecked(tile_coord, polygon))
+    }
+
+    /// Returns true if ``poly_ref`` points to an existing polygon & its tile hasn't been rebuilt or removed since the reference was created.
+    pub fn is_valid_poly_ref(&self, poly_ref: PolyRef) -> bool {
+        self.get_tile_and_polygon(poly_ref).is_some()
+    }
+
+    /// Returns the tile & polygon ``poly_ref`` points to. **``None``** if ``poly_ref`` isn't valid, see [NavMeshTiles::is_valid_poly_ref].
+    pub fn get_tile_and_polygon(&self, poly_ref: PolyRef) -> Option<(&NavMeshTile, &Polygon)> {
+        if poly_ref.salt != self.get_tile_salt(poly_ref.tile) {
+            return None;
+        }
+
+        let tile = self.tiles.get(&poly_ref.tile)?;
+        let polygon = tile.polygons.get(poly_ref.polygon as usize)?;
+
+        Some((tile, polygon))
+    }
+
+    /// Creates a [PolyRef] without checking that the polygon exists.
+    pub(crate) fn poly_ref_unchecked(&self, tile_coord: UVec2, polygon: u16) -> PolyRef {
+        PolyRef::new(tile_coord, polygon, self.get_tile_salt(tile_coord))
+    }
+
+    pub(crate) fn get_tile_salt(&self, tile_coord: UVec2) -> u32 {
+        *self.tile_salts.get(&tile_coord).unwrap_or(&0)
+    }
+
+    /// Invalidates every [PolyRef] to the tile at ``tile_coord``.
+    fn bump_tile_salt(&mut self, tile_coord: UVec2) {
+        let salt = self.tile_salts.entry(tile_coord).or_default();
+        // Wrapping is fine, the salt only has to differ from the previous one.
+        *salt = salt.wrapping_add(1);
+    }
+
+    /// Returns the flags of the polygon ``poly_ref`` points to. **``None``** if ``poly_ref`` isn't valid.
+    pub fn get_polygon_flags(&self, poly_ref: PolyRef) -> Option<u16> {
+        self.get_tile_and_polygon(poly_ref)
+            .map(|(tile, _)| tile.flags[poly_ref.polygon as usize])
+    }
+
+    /// Sets the flags of the polygon ``poly_ref`` points to. Returns false if ``poly_ref`` isn't valid.
+    ///
+    /// This is overwritten by the flags from [crate::NavMeshPolygonFlags] when the tile is regenerated.
+    ///
+    /// Doesn't bump the tile's generation or salt, so flag edits are invisible to sliced searches ([crate::query::SlicedFindPathError::NavMeshChanged]), [crate::agent::NavPath::is_outdated] & ticket generations. Re-request any paths that may be affected yourself.
+    pub fn set_polygon_flags(&mut self, poly_ref: PolyRef, flags: u16) -> bool {
+        if !self.is_valid_poly_ref(poly_ref) {
+            return false;
+        }
+        let Some(tile) = self.tiles.get_mut(&poly_ref.tile) else {
+            return false;
+        };
+
+        tile.flags[poly_ref.polygon as usize] = flags;
+
+        true
+    }
+
+    /// Inserts ``tile`` at ``tile_coord``, replacing any existing tile, & links it up with its neighbouring tiles.
+    ///
+    /// This is done automatically when tiles are generated by the plugin. Mainly useful for building nav-meshes by hand with [crate::build_tile_sync].
+    pub fn add_tile(
         &mut self,
         tile_coord: UVec2,
         mut tile: NavMeshTile,
@@ -203,9 +479,14 @@ impl NavMeshTiles {
 
         // Insert tile.
         self.tiles.insert(tile_coord, tile);
+        self.bump_tile_salt(tile_coord);
+
+        self.rebuild_abstract_graph_around(tile_coord);
+        self.update_islands_around(tile_coord);
     }
 
-    pub(super) fn remove_tile(&mut self, tile_coord: UVec2) {
+    /// Removes the tile at ``tile_coord`` & any links neighbouring tiles have to it.
+    pub fn remove_tile(&mut self, tile_coord: UVec2) {
         if tile_coord.x > 0 {
             let direction = EdgeConnectionDirection::XNegative;
             let neighbour_coord = direction.offset(tile_coord);
@@ -243,17 +524,230 @@ impl NavMeshTiles {
         }
 
         self.tiles.remove(&tile_coord);
+        self.bump_tile_salt(tile_coord);
+
+        self.rebuild_abstract_graph_around(tile_coord);
+        self.update_islands_around(tile_coord);
+    }
+
+    /// Rebuilds the [AbstractGraph] nodes of the tile at ``tile_coord`` & the nodes of its neighbours facing it, whose border links may have changed.
+    fn rebuild_abstract_graph_around(&mut self, tile_coord: UVec2) {
+        self.abstract_graph
+            .rebuild_tile(tile_coord, self.tiles.get(&tile_coord));
+
+        for (neighbour_coord, direction) in neighbours_of(tile_coord) {
+            if let Some(neighbour) = self.tiles.get(&neighbour_coord) {
+                self.abstract_graph.rebuild_tile_side(
+                    neighbour_coord,
+                    neighbour,
+                    direction.opposite(),
+                );
+            }
+        }
     }
 
-    /// Returns the closest polygon in a box around ``center`` as a tuple of (tile coordinate, polygon index, position on triangle).
+    /// Recomputes the connected components of the tile at ``tile_coord`` & relabels every island touching it or its neighbours with new island ids.
+    ///
+    /// Any island that could have been merged or split by the tile changing has a component in one of these tiles, so islands elsewhere keep their ids.
+    /// Relabelling walks the links between components rather than polygons, so it only costs about as much as the number of tiles on the affected islands.
+    fn update_islands_around(&mut self, tile_coord: UVec2) {
+        match self.tiles.get(&tile_coord) {
+            Some(tile) => {
+                let polygon_components = find_polygon_components(tile);
+                let component_count = polygon_components
+                    .iter()
+                    .max()
+                    .map_or(0, |component| *component as usize + 1);
+
+                self.islands.insert(
+                    tile_coord,
+                    TileIslands {
+                        polygon_components,
+                        component_islands: vec![0; component_count],
+                        component_links: Vec::new(),
+                    },
+                );
+            }
+            None => {
+                self.islands.remove(&tile_coord);
+            }
+        }
+
+        // The border links of the tile & its neighbours may have changed.
+        for coord in tile_and_neighbours(tile_coord) {
+            let Some(component_links) = self.find_component_links(coord) else {
+                continue;
+            };
+            if let Some(tile_islands) = self.islands.get_mut(&coord) {
+                tile_islands.component_links = component_links;
+            }
+        }
+
+        let mut visited = HashSet::new();
+        let mut stack = Vec::new();
+        for coord in tile_and_neighbours(tile_coord) {
+            let Some(component_count) = self
+                .islands
+                .get(&coord)
+                .map(|tile_islands| tile_islands.component_islands.len())
+            else {
+                continue;
+            };
+
+            for component in 0..component_count as u16 {
+                if !visited.insert((coord, component)) {
+                    continue;
+                }
+
+                let island = self.next_island;
+                self.next_island = self.next_island.wrapping_add(1);
+
+                stack.push((coord, component));
+                while let Some((coord, component)) = stack.pop() {
+                    let Some(tile_islands) = self.islands.get_mut(&coord) else {
+                        continue;
+                    };
+                    tile_islands.component_islands[component as usize] = island;
+
+                    for neighbour in tile_islands.component_links[component as usize].iter() {
+                        if visited.insert(*neighbour) {
+                            stack.push(*neighbour);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the components in neighbouring tiles every component of the tile at ``tile_coord`` links to. **``None``** if the tile doesn't exist.
+    fn find_component_links(&self, tile_coord: UVec2) -> Option<Vec<Vec<(UVec2, u16)>>> {
+        let tile = self.tiles.get(&tile_coord)?;
+        let tile_islands = self.islands.get(&tile_coord)?;
+
+        let mut component_links = vec![Vec::new(); tile_islands.component_islands.len()];
+        for (polygon, component) in tile.polygons.iter().zip(&tile_islands.polygon_components) {
+            for link in polygon.links.iter() {
+                if !matches!(link, Link::External { .. }) {
+                    continue;
+                }
+
+                let (neighbour_coord, neighbour_polygon) = link.neighbour(tile_coord);
+                let Some(neighbour_component) =
+                    self.islands.get(&neighbour_coord).and_then(|neighbour| {
+                        neighbour.polygon_components.get(neighbour_polygon as usize)
+                    })
+                else {
+                    continue;
+                };
+
+                let links: &mut Vec<_> = &mut component_links[*component as usize];
+                if !links.contains(&(neighbour_coord, *neighbour_component)) {
+                    links.push((neighbour_coord, *neighbour_component));
+                }
+            }
+        }
+
+        Some(component_links)
+    }
+
+    /// Returns the island ``poly_ref`` is part of. **``None``** if ``poly_ref`` isn't valid, see [NavMeshTiles::is_valid_poly_ref].
+    ///
+    /// An island is a set of polygons connected by links, two polygons on the same island can reach each other (ignoring any [QueryFilter]).
+    /// Island ids are only stable until a tile touching the island is added or removed.
+    pub fn island_of(&self, poly_ref: PolyRef) -> Option<u32> {
+        if !self.is_valid_poly_ref(poly_ref) {
+            return None;
+        }
+
+        let tile_islands = self.islands.get(&poly_ref.tile)?;
+        let component = tile_islands
+            .polygon_components
+            .get(poly_ref.polygon as usize)?;
+
+        tile_islands
+            .component_islands
+            .get(*component as usize)
+            .copied()
+    }
+
+    /// Returns true if a path exists between ``a`` & ``b``, ignoring any [QueryFilter]. False if either of them isn't valid.
+    ///
+    /// Use this to skip pathfinding toward unreachable destinations, which otherwise searches every polygon reachable from the start before giving up.
+    pub fn are_connected(&self, a: PolyRef, b: PolyRef) -> bool {
+        match (self.island_of(a), self.island_of(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Returns the height of the nav-mesh surface at the XZ coordinates of ``position`` as a tuple of (polygon, height).
+    ///
+    /// On multi-floor levels the surface with the height closest to ``position.y`` is returned. Returns **``None``** if there is no surface at the position.
+    ///
+    /// * ``vertical_search`` - Maximum distance above & below ``position.y`` to search for a surface.
+    pub fn get_height_at(
+        &self,
+        nav_mesh_settings: &NavMeshSettings,
+        position: Vec3,
+        vertical_search: f32,
+    ) -> Option<(PolyRef, f32)> {
+        let tile_coord = nav_mesh_settings.get_tile_containing_position(position.xz());
+        let tile = self.tiles.get(&tile_coord)?;
+
+        let min = Vec3::new(position.x, position.y - vertical_search, position.z);
+        let max = Vec3::new(position.x, position.y + vertical_search, position.z);
+
+        let mut out_height = None;
+        let mut out_distance = f32::INFINITY;
+        tile.query_polygons_in_box(min, max, |poly_i| {
+            let vertices = tile.get_polygon_vertices(&tile.polygons[poly_i as usize]);
+            let Some(height) = get_height_in_triangle(&vertices, position) else {
+                return;
+            };
+
+            let distance = (height - position.y).abs();
+            if distance <= vertical_search && distance < out_distance {
+                out_distance = distance;
+                out_height = Some((poly_i, height));
+            }
+        });
+
+        out_height.map(|(poly_i, height)| (self.poly_ref_unchecked(tile_coord, poly_i), height))
+    }
+
+    /// Returns the closest polygon in a box around ``center`` as a tuple of (polygon, position on triangle).
+    ///
+    /// Equivalent to [NavMeshTiles::project_point] with the same extents on every axis.
+    ///
+    /// * ``filter`` - Polygons not passing the filter are ignored. If **``None``** all polygons are considered.
     pub fn find_closest_polygon_in_box(
         &self,
         nav_mesh_settings: &NavMeshSettings,
         center: Vec3,
         half_extents: f32,
-    ) -> Option<(UVec2, u16, Vec3)> {
-        let min = center - half_extents;
-        let max = center + half_extents;
+        filter: Option<&dyn QueryFilter>,
+    ) -> Option<(PolyRef, Vec3)> {
+        self.project_point(nav_mesh_settings, center, Vec3::splat(half_extents), filter)
+            .map(|projected| (projected.polygon, projected.position))
+    }
+
+    /// Projects ``position`` onto the closest polygon in a box around it. Returns **``None``** if no polygon is in the box.
+    ///
+    /// Use a smaller vertical extent than horizontal extent on multi-floor levels to keep positions from being projected onto the floor above or below.
+    ///
+    /// * ``position`` - Position to project.
+    /// * ``extents`` - Half extents of the box to search in. In world units.
+    /// * ``filter`` - Polygons not passing the filter are ignored. If **``None``** all polygons are considered.
+    pub fn project_point(
+        &self,
+        nav_mesh_settings: &NavMeshSettings,
+        position: Vec3,
+        extents: Vec3,
+        filter: Option<&dyn QueryFilter>,
+    ) -> Option<ProjectedPoint> {
+        let center = position;
+        let min = center - extents;
+        let max = center + extents;
 
         let min_tile = nav_mesh_settings.get_tile_containing_position(min.xz());
         let max_tile = nav_mesh_settings.get_tile_containing_position(max.xz());
@@ -264,20 +758,37 @@ impl NavMeshTiles {
             for y in min_tile.y..=max_tile.y {
                 let tile_coords = UVec2::new(x, y);
                 if let Some(tile) = self.tiles.get(&tile_coords) {
-                    for (poly_i, polygon) in tile.polygons.iter().enumerate() {
+                    tile.query_polygons_in_box(min, max, |poly_i| {
+                        if filter.is_some_and(|filter| {
+                            !tile.polygon_passes_filter(tile_coords, poly_i, filter)
+                        }) {
+                            return;
+                        }
+
+                        let polygon = &tile.polygons[poly_i as usize];
                         let closest_point = tile.get_closest_point_in_polygon(polygon, center);
+                        // Positions on the polygon's edges count as over it, the closest point is then below or above them as well.
+                        let is_over_polygon = closest_point.xz().distance_squared(center.xz())
+                            <= OVER_POLYGON_EPSILON;
                         let closest_distance = closest_point.distance_squared(center);
 
                         if closest_distance < out_distance {
                             out_distance = closest_distance;
-                            out_polygon = Some((tile_coords, poly_i as u16, closest_point));
+                            out_polygon =
+                                Some((tile_coords, poly_i, closest_point, is_over_polygon));
                         }
-                    }
+                    });
                 }
             }
         }
 
-        out_polygon
+        out_polygon.map(
+            |(tile_coords, poly_i, closest_point, is_over_polygon)| ProjectedPoint {
+                polygon: self.poly_ref_unchecked(tile_coords, poly_i),
+                position: closest_point,
+                is_over_polygon,
+            },
+        )
     }
 }
 
@@ -347,7 +858,7 @@ fn closest_point_on_edges(vertices: &[Vec3; VERTICES_IN_TRIANGLE], position: Vec
     edge_min.lerp(edge_max, t_min)
 }
 
-fn distance_point_to_segment_2d(point: Vec3, seg_a: Vec3, seg_b: Vec3) -> (f32, f32) {
+pub(crate) fn distance_point_to_segment_2d(point: Vec3, seg_a: Vec3, seg_b: Vec3) -> (f32, f32) {
     let ba_x = seg_b.x - seg_a.x;
     let ba_z = seg_b.z - seg_a.z;
 
@@ -367,7 +878,7 @@ fn distance_point_to_segment_2d(point: Vec3, seg_a: Vec3, seg_b: Vec3) -> (f32,
     (dx * dx + dz * dz, t)
 }
 
-fn in_polygon(vertices: &[Vec3; VERTICES_IN_TRIANGLE], position: Vec3) -> bool {
+pub(crate) fn in_polygon(vertices: &[Vec3; VERTICES_IN_TRIANGLE], position: Vec3) -> bool {
     let mut inside = false;
 
     for i in 0..vertices.len() {
@@ -633,7 +1144,7 @@ pub(super) fn create_nav_mesh_tile_from_poly_mesh(
 
             Polygon { links, indices }
         })
-        .collect();
+        .collect::<Box<[Polygon]>>();
 
     let tile_origin = nav_mesh_settings.get_tile_origin_with_border(tile_coord);
     let vertices = poly_mesh
@@ -647,12 +1158,157 @@ pub(super) fn create_nav_mesh_tile_from_poly_mesh(
                 tile_origin.y + vertex.z as f32 * nav_mesh_settings.cell_width,
             )
         })
-        .collect();
+        .collect::<Box<[Vec3]>>();
+
+    let bv_tree = build_bv_tree(&vertices, &polygons);
+    let (min_height, max_height) = vertices
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), vertex| {
+            (min.min(vertex.y), max.max(vertex.y))
+        });
 
     NavMeshTile {
         vertices,
         edges: poly_mesh.edges.into_boxed_slice(),
         polygons,
         areas: poly_mesh.areas.into_boxed_slice(),
+        flags: poly_mesh.flags.into_boxed_slice(),
+        bv_tree,
+        min_height,
+        max_height,
+    }
+}
+
+struct BvItem {
+    min: Vec3,
+    max: Vec3,
+    polygon: u16,
+}
+
+fn build_bv_tree(vertices: &[Vec3], polygons: &[Polygon]) -> Box<[BvNode]> {
+    let mut items = polygons
+        .iter()
+        .enumerate()
+        .map(|(i, polygon)| {
+            let polygon_vertices = polygon.indices.map(|index| vertices[index as usize]);
+
+            BvItem {
+                min: polygon_vertices[0]
+                    .min(polygon_vertices[1])
+                    .min(polygon_vertices[2]),
+                max: polygon_vertices[0]
+                    .max(polygon_vertices[1])
+                    .max(polygon_vertices[2]),
+                polygon: i as u16,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut nodes = Vec::with_capacity(polygons.len() * 2);
+    if !items.is_empty() {
+        subdivide_bv_items(&mut items, &mut nodes);
+    }
+
+    nodes.into_boxed_slice()
+}
+
+fn subdivide_bv_items(items: &mut [BvItem], nodes: &mut Vec<BvNode>) {
+    if let [item] = items {
+        nodes.push(BvNode {
+            min: item.min,
+            max: item.max,
+            polygon: Some(item.polygon),
+            escape: 1,
+        });
+        return;
     }
+
+    let (min, max) = items
+        .iter()
+        .fold((Vec3::INFINITY, Vec3::NEG_INFINITY), |(min, max), item| {
+            (min.min(item.min), max.max(item.max))
+        });
+
+    let node_index = nodes.len();
+    nodes.push(BvNode {
+        min,
+        max,
+        polygon: None,
+        escape: 0,
+    });
+
+    // Split along the longest axis.
+    let extents = max - min;
+    let axis = if extents.x >= extents.y && extents.x >= extents.z {
+        0
+    } else if extents.y >= extents.z {
+        1
+    } else {
+        2
+    };
+
+    let split = items.len() / 2;
+    items.select_nth_unstable_by(split, |a, b| {
+        (a.min[axis] + a.max[axis]).total_cmp(&(b.min[axis] + b.max[axis]))
+    });
+
+    let (left, right) = items.split_at_mut(split);
+    subdivide_bv_items(left, nodes);
+    subdivide_bv_items(right, nodes);
+
+    nodes[node_index].escape = (nodes.len() - node_index) as u32;
+}
+
+/// Returns the component of every polygon in ``tile``, polygons are in the same component if they're connected through internal links.
+fn find_polygon_components(tile: &NavMeshTile) -> Vec<u16> {
+    let mut polygon_components = vec![u16::MAX; tile.polygons.len()];
+    let mut component_count = 0;
+    let mut stack = Vec::new();
+
+    for start in 0..tile.polygons.len() {
+        if polygon_components[start] != u16::MAX {
+            continue;
+        }
+
+        polygon_components[start] = component_count;
+        stack.push(start);
+        while let Some(polygon) = stack.pop() {
+            for link in tile.polygons[polygon].links.iter() {
+                let Link::Internal {
+                    neighbour_polygon, ..
+                } = link
+                else {
+                    continue;
+                };
+
+                let neighbour = *neighbour_polygon as usize;
+                if polygon_components[neighbour] == u16::MAX {
+                    polygon_components[neighbour] = component_count;
+                    stack.push(neighbour);
+                }
+            }
+        }
+
+        component_count += 1;
+    }
+
+    polygon_components
+}
+
+/// Returns the coordinates of the (up to) 4 neighbours of ``tile_coord`` & the direction they're in.
+fn neighbours_of(tile_coord: UVec2) -> impl Iterator<Item = (UVec2, EdgeConnectionDirection)> {
+    [
+        (tile_coord.x > 0, EdgeConnectionDirection::XNegative),
+        (tile_coord.y < u32::MAX, EdgeConnectionDirection::ZPositive),
+        (tile_coord.x < u32::MAX, EdgeConnectionDirection::XPositive),
+        (tile_coord.y > 0, EdgeConnectionDirection::ZNegative),
+    ]
+    .into_iter()
+    .filter(|(in_bounds, _)| *in_bounds)
+    .map(move |(_, direction)| (direction.offset(tile_coord), direction))
+}
+
+/// Returns ``tile_coord`` followed by the coordinates of its (up to) 4 neighbours.
+pub(crate) fn tile_and_neighbours(tile_coord: UVec2) -> impl Iterator<Item = UVec2> {
+    std::iter::once(tile_coord).chain(neighbours_of(tile_coord).map(|(coord, _)| coord))
 }
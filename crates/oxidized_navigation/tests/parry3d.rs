@@ -1,12 +1,35 @@
-use std::{num::NonZeroU16, time::Duration};
+use std::{
+    num::{NonZeroU16, NonZeroUsize},
+    time::Duration,
+};
 
-use bevy::prelude::*;
+use bevy::{prelude::*, time::TimeUpdateStrategy};
 use oxidized_navigation::{
-    colliders::OxidizedCollider, query::find_path, ActiveGenerationTasks, NavMesh, NavMeshAffector,
+    agent::{NavAgent, NavPath, PathRequest, PathRequestFailed},
+    colliders::OxidizedCollider,
+    corridor::PathCorridor,
+    crowd::{Crowd, CrowdAgentParams},
+    flow_field::compute_flow_field,
+    path_following::{PathCompleted, PathFailed, PathFollower, PathFollowingPlugin},
+    query::{
+        find_distance_to_wall, find_path, find_polygon_path, find_polygon_path_hierarchical,
+        find_polygons_around_circle, find_polygons_around_shape, find_random_point,
+        find_random_point_around_circle, find_straight_path, move_along_surface,
+        perform_string_pulling_on_path, raycast, DefaultQueryFilter, FindPathError,
+        FindPolygonPathError, MoveAlongSurfaceError, NavMeshQuery, Path, PathStatus,
+        SlicedFindPathError, SlicedFindPathStatus, SlopeCosts, StraightPathFlags,
+        StraightPathOptions, StringPullingError,
+    },
+    tickets::{
+        PathTicket, PathTicketStatus, PathfindingFinished, PathfindingQueue, PathfindingRequest,
+    },
+    tiles::NavMeshTiles,
+    ActiveGenerationTasks, Area, NavMesh, NavMeshAffector, NavMeshAreaType, NavMeshPolygonFlags,
     NavMeshSettings, OxidizedNavigationPlugin,
 };
 use parry3d::{
     bounding_volume::Aabb,
+    math::Point,
     shape::{SharedShape, TypedShape},
 };
 
@@ -68,6 +91,53 @@ fn setup_world_system(mut commands: Commands) {
     ));
 }
 
+fn setup_island_system(mut commands: Commands) {
+    // Floating platform that can't be reached from the ground.
+    commands.spawn((
+        Transform::from_xyz(15.0, 5.0, 15.0),
+        MyParryCollider {
+            collider: SharedShape::cuboid(3.0, 0.1, 3.0),
+        },
+        NavMeshAffector,
+    ));
+}
+
+fn setup_area_strip_system(mut commands: Commands) {
+    // Strip of a different area type cutting across the whole plane.
+    commands.spawn((
+        Transform::from_xyz(10.0, 0.0, 0.0),
+        MyParryCollider {
+            collider: SharedShape::cuboid(2.0, 0.1, 25.0),
+        },
+        NavMeshAffector,
+        NavMeshAreaType(Some(Area(1))),
+    ));
+}
+
+fn setup_flag_strip_system(mut commands: Commands) {
+    // Strip with polygon flags cutting across the whole plane.
+    commands.spawn((
+        Transform::from_xyz(10.0, 0.0, 0.0),
+        MyParryCollider {
+            collider: SharedShape::cuboid(2.0, 0.1, 25.0),
+        },
+        NavMeshAffector,
+        NavMeshPolygonFlags(FLAG_DOOR),
+    ));
+}
+
+fn setup_flag_wall_system(mut commands: Commands) {
+    // Flagged strip along the z = 0 tile border, leaving a gap at the plane's positive x edge.
+    commands.spawn((
+        Transform::from_xyz(-2.5, 0.0, 0.0),
+        MyParryCollider {
+            collider: SharedShape::cuboid(22.5, 0.1, 2.0),
+        },
+        NavMeshAffector,
+        NavMeshPolygonFlags(FLAG_DOOR),
+    ));
+}
+
 fn setup_app(app: &mut App) {
     app.add_plugins((
         MinimalPlugins,
@@ -126,7 +196,1910 @@ fn test_simple_navigation() {
     // Run pathfinding to get a polygon path.
     let path = find_path(&nav_mesh, nav_mesh_settings, start_pos, end_pos, None, None);
 
-    if let Err(error) = path {
-        panic!("Pathfinding failed: {error:?}");
+    match path {
+        Ok(path) => assert!(path.status.is_complete(), "Path should be complete."),
+        Err(error) => panic!("Pathfinding failed: {error:?}"),
+    }
+}
+
+#[test]
+fn test_partial_path() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, (setup_world_system, setup_island_system));
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    let start_pos = Vec3::new(5.0, 0.1, 5.0);
+    let end_pos = Vec3::new(15.0, 5.1, 15.0);
+
+    let path = find_path(&nav_mesh, nav_mesh_settings, start_pos, end_pos, None, None).unwrap();
+
+    let PathStatus::Partial { closest_point } = path.status else {
+        panic!("Path should be partial: {:?}", path.status);
+    };
+    assert!(
+        closest_point.y < 1.0,
+        "Closest point should be on the ground."
+    );
+    assert_eq!(path.points.last(), Some(&closest_point));
+}
+
+#[test]
+fn test_raycast() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, setup_world_system);
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    // Open ground, nothing in the way.
+    let start_pos = Vec3::new(10.0, 0.1, 0.0);
+    let end_pos = Vec3::new(10.0, 0.1, 10.0);
+
+    let (polygon, start_pos) = nav_mesh
+        .find_closest_polygon_in_box(nav_mesh_settings, start_pos, 1.0, None)
+        .expect("No polygon found at start position.");
+    let result = raycast(&nav_mesh, polygon, start_pos, end_pos, None).unwrap();
+
+    assert!(result.reached_end(), "Ray was blocked: {:?}", result.hit);
+    assert_eq!(result.polygons.first(), Some(&polygon));
+
+    // Into the cube.
+    let start_pos = Vec3::new(-5.0, 0.1, 0.0);
+    let end_pos = Vec3::new(-5.0, 0.1, -10.0);
+
+    let (polygon, start_pos) = nav_mesh
+        .find_closest_polygon_in_box(nav_mesh_settings, start_pos, 1.0, None)
+        .expect("No polygon found at start position.");
+    let result = raycast(&nav_mesh, polygon, start_pos, end_pos, None).unwrap();
+
+    let hit = result.hit.expect("Ray should hit the cube.");
+    assert!(
+        hit.distance > 3.0 && hit.distance < 4.0,
+        "Unexpected hit distance: {}",
+        hit.distance
+    );
+    assert!(
+        hit.normal.dot(Vec3::Z) > 0.9,
+        "Unexpected hit normal: {}",
+        hit.normal
+    );
+}
+
+#[test]
+fn test_nav_mesh_query() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, setup_world_system);
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    let start_pos = Vec3::new(5.0, 1.0, 5.0);
+    let end_pos = Vec3::new(-15.0, 1.0, -15.0);
+
+    let expected_path =
+        find_polygon_path(&nav_mesh, nav_mesh_settings, start_pos, end_pos, None, None).unwrap();
+
+    // Reusing a query should give the same result every time.
+    let mut query = NavMeshQuery::default();
+    for _ in 0..3 {
+        let path = query
+            .find_polygon_path(&nav_mesh, nav_mesh_settings, start_pos, end_pos, None, None)
+            .unwrap();
+
+        assert_eq!(path, expected_path);
+    }
+
+    // A node limit stops the search early.
+    let mut query = NavMeshQuery::new(NonZeroUsize::new(2));
+    let path = query
+        .find_polygon_path(&nav_mesh, nav_mesh_settings, start_pos, end_pos, None, None)
+        .unwrap();
+
+    assert!(path.polygons.len() <= 2);
+    assert!(matches!(path.status, PathStatus::OutOfNodes { .. }));
+    assert_ne!(path.polygons.last(), expected_path.polygons.last());
+}
+
+#[test]
+fn test_sliced_find_path() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, setup_world_system);
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    let start_pos = Vec3::new(5.0, 1.0, 5.0);
+    let end_pos = Vec3::new(-15.0, 1.0, -15.0);
+
+    let expected_path =
+        find_polygon_path(&nav_mesh, nav_mesh_settings, start_pos, end_pos, None, None).unwrap();
+
+    let mut query = NavMeshQuery::default();
+    query
+        .init_sliced_find_path(&nav_mesh, nav_mesh_settings, start_pos, end_pos, None, None)
+        .unwrap();
+
+    let mut slices = 0;
+    while query.update_sliced_find_path(&nav_mesh, 1, None).unwrap()
+        == SlicedFindPathStatus::InProgress
+    {
+        slices += 1;
+    }
+
+    assert!(slices > 1, "Search should take more than one slice.");
+    assert_eq!(
+        query.finalize_sliced_find_path(&nav_mesh).unwrap(),
+        expected_path
+    );
+
+    // Changing a visited tile should fail the search.
+    let mut changed_nav_mesh = nav_mesh.clone();
+    query
+        .init_sliced_find_path(&nav_mesh, nav_mesh_settings, start_pos, end_pos, None, None)
+        .unwrap();
+    query.update_sliced_find_path(&nav_mesh, 1, None).unwrap();
+
+    for generation in changed_nav_mesh.tile_generations.values_mut() {
+        *generation += 1;
+    }
+
+    assert!(matches!(
+        query.update_sliced_find_path(&changed_nav_mesh, 1, None),
+        Err(SlicedFindPathError::NavMeshChanged)
+    ));
+
+    // Changing a visited tile after the search finished should fail finalizing it.
+    query
+        .init_sliced_find_path(&nav_mesh, nav_mesh_settings, start_pos, end_pos, None, None)
+        .unwrap();
+    while query.update_sliced_find_path(&nav_mesh, 1, None).unwrap()
+        == SlicedFindPathStatus::InProgress
+    {}
+
+    let start_tile = expected_path.polygons[0].tile();
+    let mut rebuilt_nav_mesh = nav_mesh.clone();
+    *rebuilt_nav_mesh
+        .tile_generations
+        .get_mut(&start_tile)
+        .unwrap() += 1;
+    rebuilt_nav_mesh.add_tile(
+        start_tile,
+        nav_mesh.tiles[&start_tile].clone(),
+        nav_mesh_settings,
+    );
+
+    assert!(matches!(
+        query.finalize_sliced_find_path(&rebuilt_nav_mesh),
+        Err(SlicedFindPathError::NavMeshChanged)
+    ));
+    assert!(matches!(
+        query.finalize_sliced_find_path(&nav_mesh),
+        Err(SlicedFindPathError::NotInitialized)
+    ));
+}
+
+#[test]
+fn test_query_filter() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, (setup_world_system, setup_area_strip_system));
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    let start_pos = Vec3::new(5.0, 0.1, 0.0);
+    let end_pos = Vec3::new(15.0, 0.1, 0.0);
+
+    // Expensive areas are still traversed when they are the only way.
+    let filter = DefaultQueryFilter::default().with_area_cost(Area(1), 10.0);
+    let path = find_path(
+        &nav_mesh,
+        nav_mesh_settings,
+        start_pos,
+        end_pos,
+        None,
+        Some(&filter),
+    )
+    .unwrap();
+    assert!(path.status.is_complete());
+
+    // Excluded areas are never traversed.
+    let filter = DefaultQueryFilter::default().with_excluded_area(Area(1));
+    let path = find_path(
+        &nav_mesh,
+        nav_mesh_settings,
+        start_pos,
+        end_pos,
+        None,
+        Some(&filter),
+    )
+    .unwrap();
+    assert!(matches!(path.status, PathStatus::Partial { .. }));
+    assert!(path.points.iter().all(|point| point.x <= 8.0));
+}
+
+const FLAG_DOOR: u16 = 1 << 0;
+
+#[test]
+fn test_polygon_flags() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, (setup_world_system, setup_flag_strip_system));
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let mut nav_mesh = nav_mesh.write().expect("Failed to get nav-mesh lock.");
+
+    let start_pos = Vec3::new(5.0, 0.1, 0.0);
+    let end_pos = Vec3::new(15.0, 0.1, 0.0);
+
+    let flagged_polygons = nav_mesh
+        .tiles
+        .iter()
+        .flat_map(|(tile_coord, tile)| {
+            tile.flags
+                .iter()
+                .enumerate()
+                .filter(|(_, flags)| **flags & FLAG_DOOR != 0)
+                .map(|(polygon, _)| nav_mesh.get_poly_ref(*tile_coord, polygon as u16).unwrap())
+        })
+        .collect::<Vec<_>>();
+    assert!(!flagged_polygons.is_empty());
+
+    // Polygons with excluded flags are never traversed.
+    let filter = DefaultQueryFilter::default().with_excluded_flags(FLAG_DOOR);
+    let path = find_path(
+        &nav_mesh,
+        nav_mesh_settings,
+        start_pos,
+        end_pos,
+        None,
+        Some(&filter),
+    )
+    .unwrap();
+    assert!(matches!(path.status, PathStatus::Partial { .. }));
+
+    // Nearest polygon queries ignore polygons not passing the filter.
+    let filter = DefaultQueryFilter::default().with_included_flags(FLAG_DOOR);
+    let (polygon, _) = nav_mesh
+        .find_closest_polygon_in_box(nav_mesh_settings, start_pos, 5.0, Some(&filter))
+        .expect("No flagged polygon found.");
+    assert!(flagged_polygons.contains(&polygon));
+
+    // Flags can be changed at runtime.
+    for polygon in flagged_polygons {
+        assert!(nav_mesh.set_polygon_flags(polygon, 0));
+        assert_eq!(nav_mesh.get_polygon_flags(polygon), Some(0));
+    }
+
+    let filter = DefaultQueryFilter::default().with_excluded_flags(FLAG_DOOR);
+    let path = find_path(
+        &nav_mesh,
+        nav_mesh_settings,
+        start_pos,
+        end_pos,
+        None,
+        Some(&filter),
+    )
+    .unwrap();
+    assert!(path.status.is_complete());
+}
+
+/// Small deterministic random number generator so tests don't need a dependency.
+fn test_random(seed: u32) -> impl FnMut() -> f32 {
+    let mut state = seed;
+    move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1 << 24) as f32
+    }
+}
+
+#[test]
+fn test_random_point() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, setup_world_system);
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    let mut random = test_random(42);
+    let mut points = Vec::new();
+    for _ in 0..32 {
+        let (polygon, point) =
+            find_random_point(&nav_mesh, None, &mut random).expect("No random point found.");
+
+        let (tile, polygon) = nav_mesh.get_tile_and_polygon(polygon).unwrap();
+        let closest_point = tile.get_closest_point_in_polygon(polygon, point);
+        assert!(
+            closest_point.distance(point) < 0.01,
+            "Random point isn't on its polygon."
+        );
+        assert!(point.x.abs() <= 25.0 && point.z.abs() <= 25.0);
+
+        points.push(point);
+    }
+    assert!(points.windows(2).any(|points| points[0] != points[1]));
+
+    // Around circle.
+    let start_pos = Vec3::new(5.0, 0.1, 5.0);
+    let (start_polygon, start_pos) = nav_mesh
+        .find_closest_polygon_in_box(nav_mesh_settings, start_pos, 1.0, None)
+        .expect("No polygon found at start position.");
+
+    for _ in 0..32 {
+        let (polygon, point) = find_random_point_around_circle(
+            &nav_mesh,
+            start_polygon,
+            start_pos,
+            2.0,
+            None,
+            &mut random,
+        )
+        .unwrap();
+
+        let (tile, polygon) = nav_mesh.get_tile_and_polygon(polygon).unwrap();
+        let closest_point = tile.get_closest_point_in_polygon(polygon, point);
+        assert!(
+            closest_point.distance(point) < 0.01,
+            "Random point isn't on its polygon."
+        );
+    }
+}
+
+#[test]
+fn test_bv_tree() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, setup_world_system);
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    let boxes = [
+        (Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0)),
+        (Vec3::new(-6.0, 0.0, -6.0), Vec3::new(-4.0, 3.0, -4.0)),
+        (Vec3::new(10.0, -5.0, -20.0), Vec3::new(20.0, 5.0, 20.0)),
+        (Vec3::new(-50.0, -50.0, -50.0), Vec3::new(50.0, 50.0, 50.0)),
+        (Vec3::new(0.0, 10.0, 0.0), Vec3::new(5.0, 20.0, 5.0)),
+    ];
+
+    for tile in nav_mesh.tiles.values() {
+        for (min, max) in boxes {
+            let mut polygons = Vec::new();
+            tile.query_polygons_in_box(min, max, |polygon| polygons.push(polygon));
+            polygons.sort();
+
+            // Brute force over every polygon.
+            let expected = tile
+                .polygons
+                .iter()
+                .enumerate()
+                .filter(|(_, polygon)| {
+                    let vertices = tile.get_polygon_vertices(polygon);
+                    let polygon_min = vertices[0].min(vertices[1]).min(vertices[2]);
+                    let polygon_max = vertices[0].max(vertices[1]).max(vertices[2]);
+
+                    polygon_min.cmple(max).all() && polygon_max.cmpge(min).all()
+                })
+                .map(|(i, _)| i as u16)
+                .collect::<Vec<_>>();
+
+            assert_eq!(polygons, expected);
+        }
+    }
+}
+
+#[test]
+fn test_move_along_surface() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, setup_world_system);
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    // Unobstructed movement reaches the end.
+    let start_pos = Vec3::new(0.0, 0.1, 0.0);
+    let end_pos = Vec3::new(1.0, 0.1, 1.0);
+    let (polygon, start_pos) = nav_mesh
+        .find_closest_polygon_in_box(nav_mesh_settings, start_pos, 1.0, None)
+        .expect("No polygon found at start position.");
+    let result = move_along_surface(&nav_mesh, polygon, start_pos, end_pos, None).unwrap();
+
+    assert!(result.position.xz().distance(end_pos.xz()) < 0.001);
+    assert_eq!(result.polygons.first(), Some(&polygon));
+
+    // Movement into the cube stops at its edge.
+    let start_pos = Vec3::new(-5.0, 0.1, -2.0);
+    let end_pos = Vec3::new(-5.0, 0.1, -4.5);
+    let (polygon, start_pos) = nav_mesh
+        .find_closest_polygon_in_box(nav_mesh_settings, start_pos, 1.0, None)
+        .expect("No polygon found at start position.");
+    let result = move_along_surface(&nav_mesh, polygon, start_pos, end_pos, None).unwrap();
+
+    assert!(
+        result.position.z > -3.75 && result.position.z < -3.0,
+        "Unexpected position: {}",
+        result.position
+    );
+    assert!((result.position.x + 5.0).abs() < 0.5);
+    assert_eq!(result.polygons.first(), Some(&polygon));
+}
+
+#[test]
+fn test_find_distance_to_wall() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, setup_world_system);
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    // In front of the cube (which covers -6.5 to -3.5 after erosion).
+    let position = Vec3::new(-5.0, 0.1, -2.5);
+    let (polygon, position) = nav_mesh
+        .find_closest_polygon_in_box(nav_mesh_settings, position, 1.0, None)
+        .expect("No polygon found at position.");
+
+    let wall = find_distance_to_wall(&nav_mesh, polygon, position, 5.0, None)
+        .unwrap()
+        .expect("Cube should be within radius.");
+    assert!(
+        (wall.distance - 1.0).abs() < 0.3,
+        "Unexpected distance: {}",
+        wall.distance
+    );
+    assert!(wall.normal.z > 0.9, "Unexpected normal: {}", wall.normal);
+
+    // The wall is a boundary edge of its polygon, running along the cube's side.
+    let (tile, wall_polygon) = nav_mesh.get_tile_and_polygon(wall.polygon).unwrap();
+    assert_eq!(tile.get_polygon_edge(wall_polygon, wall.edge), wall.segment);
+    assert!(wall_polygon
+        .links
+        .iter()
+        .all(|link| link.edge() != wall.edge));
+    let (a, b) = wall.segment;
+    assert!((a.z - wall.position.z).abs() < 0.01 && (b.z - wall.position.z).abs() < 0.01);
+    assert!(a.x.min(b.x) <= wall.position.x && wall.position.x <= a.x.max(b.x));
+
+    // No walls within a small radius.
+    let wall = find_distance_to_wall(&nav_mesh, polygon, position, 0.5, None).unwrap();
+    assert!(wall.is_none());
+}
+
+#[test]
+fn test_find_polygons_around() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, (setup_world_system, setup_island_system));
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    let center = Vec3::new(0.0, 0.1, 0.0);
+    let (polygon, center) = nav_mesh
+        .find_closest_polygon_in_box(nav_mesh_settings, center, 1.0, None)
+        .expect("No polygon found at center.");
+
+    let small = find_polygons_around_circle(&nav_mesh, polygon, center, 2.0, None).unwrap();
+    let large = find_polygons_around_circle(&nav_mesh, polygon, center, 100.0, None).unwrap();
+
+    assert_eq!(small[0].polygon, polygon);
+    assert!(small[0].parent.is_none());
+    assert!(small.len() < large.len());
+
+    for reached in large.iter().skip(1) {
+        let parent = reached
+            .parent
+            .expect("Only the start polygon has no parent.");
+        let parent = large
+            .iter()
+            .find(|other| other.polygon == parent)
+            .expect("Parent wasn't reached.");
+        assert!(parent.cost <= reached.cost);
     }
+
+    // The island isn't connected to the plane so it can never be reached.
+    let (island_polygon, _) = nav_mesh
+        .find_closest_polygon_in_box(nav_mesh_settings, Vec3::new(15.0, 5.1, 15.0), 1.0, None)
+        .expect("No polygon found on island.");
+    assert!(large
+        .iter()
+        .all(|reached| reached.polygon != island_polygon));
+
+    let shape = [
+        Vec3::new(-2.0, 0.1, -2.0),
+        Vec3::new(2.0, 0.1, -2.0),
+        Vec3::new(2.0, 0.1, 2.0),
+        Vec3::new(-2.0, 0.1, 2.0),
+    ];
+    let around_shape = find_polygons_around_shape(&nav_mesh, polygon, &shape, None).unwrap();
+    assert_eq!(around_shape[0].polygon, polygon);
+    assert!(around_shape.len() < large.len());
+}
+
+#[test]
+fn test_get_height_at() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, (setup_world_system, setup_island_system));
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    // Under the island there are two floors. The closest one is picked.
+    let (_, ground_height) = nav_mesh
+        .get_height_at(nav_mesh_settings, Vec3::new(15.0, 1.0, 15.0), 10.0)
+        .expect("No surface found below the island.");
+    assert!(
+        (ground_height - 0.1).abs() < 0.2,
+        "Unexpected height: {ground_height}"
+    );
+
+    let (_, island_height) = nav_mesh
+        .get_height_at(nav_mesh_settings, Vec3::new(15.0, 4.0, 15.0), 10.0)
+        .expect("No surface found on the island.");
+    assert!(
+        (island_height - 5.1).abs() < 0.2,
+        "Unexpected height: {island_height}"
+    );
+
+    // Out of vertical range.
+    assert!(nav_mesh
+        .get_height_at(nav_mesh_settings, Vec3::new(0.0, 3.0, 0.0), 1.0)
+        .is_none());
+
+    // Inside the cube there is no surface.
+    assert!(nav_mesh
+        .get_height_at(nav_mesh_settings, Vec3::new(-5.0, 0.1, -5.0), 1.0)
+        .is_none());
+}
+
+#[test]
+fn test_straight_path() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, (setup_world_system, setup_area_strip_system));
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    // Crosses the area strip.
+    let start_pos = Vec3::new(5.0, 0.1, 3.0);
+    let end_pos = Vec3::new(15.0, 0.1, 3.0);
+    let path =
+        find_polygon_path(&nav_mesh, nav_mesh_settings, start_pos, end_pos, None, None).unwrap();
+
+    // Without options the points match regular string pulling.
+    let straight_path = find_straight_path(
+        &nav_mesh,
+        start_pos,
+        end_pos,
+        &path.polygons,
+        StraightPathOptions::default(),
+    )
+    .unwrap();
+    let points =
+        perform_string_pulling_on_path(&nav_mesh, start_pos, end_pos, &path.polygons).unwrap();
+    assert_eq!(
+        straight_path
+            .points
+            .iter()
+            .map(|point| point.position)
+            .collect::<Vec<_>>(),
+        points
+    );
+
+    let first = straight_path.points.first().unwrap();
+    let last = straight_path.points.last().unwrap();
+    assert!(first.flags.contains(StraightPathFlags::START));
+    assert!(last.flags.contains(StraightPathFlags::END));
+    assert_eq!(first.polygon, path.polygons[0]);
+    assert_eq!(last.polygon, *path.polygons.last().unwrap());
+
+    let straight_path = find_straight_path(
+        &nav_mesh,
+        start_pos,
+        end_pos,
+        &path.polygons,
+        StraightPathOptions {
+            area_crossings: true,
+            tile_crossings: false,
+        },
+    )
+    .unwrap();
+
+    let area_changes = straight_path
+        .points
+        .iter()
+        .filter(|point| point.flags.contains(StraightPathFlags::AREA_CHANGE))
+        .collect::<Vec<_>>();
+    assert_eq!(area_changes.len(), 2, "{:?}", straight_path.points);
+    assert_eq!(area_changes[0].area, Area(1));
+    assert_eq!(area_changes[1].area, Area(0));
+    assert!((area_changes[0].position.x - 8.0).abs() < 0.5);
+    assert!((area_changes[1].position.x - 12.0).abs() < 0.5);
+
+    // Crosses the tile seam at x = 0.
+    let start_pos = Vec3::new(-5.0, 0.1, 3.0);
+    let end_pos = Vec3::new(5.0, 0.1, 3.0);
+    let path =
+        find_polygon_path(&nav_mesh, nav_mesh_settings, start_pos, end_pos, None, None).unwrap();
+    let straight_path = find_straight_path(
+        &nav_mesh,
+        start_pos,
+        end_pos,
+        &path.polygons,
+        StraightPathOptions {
+            area_crossings: false,
+            tile_crossings: true,
+        },
+    )
+    .unwrap();
+
+    let tile_crossing = straight_path
+        .points
+        .iter()
+        .find(|point| point.flags.contains(StraightPathFlags::TILE_CROSSING))
+        .expect("Path should cross a tile.");
+    assert!(tile_crossing.position.x.abs() < 0.01);
+}
+
+#[test]
+fn test_path_corridor() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, setup_world_system);
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    // Goes around the cube.
+    let start_pos = Vec3::new(-9.0, 0.1, -5.0);
+    let end_pos = Vec3::new(-1.0, 0.1, -5.0);
+    let path =
+        find_polygon_path(&nav_mesh, nav_mesh_settings, start_pos, end_pos, None, None).unwrap();
+
+    let mut corridor = PathCorridor::new(path.polygons[0], start_pos);
+    corridor.set_corridor(end_pos, &path.polygons);
+    assert_eq!(corridor.polygons(), path.polygons.as_slice());
+    assert!(corridor.is_valid(&nav_mesh, usize::MAX, None));
+
+    let mut query = NavMeshQuery::new(None);
+    corridor.optimize_path_topology(&mut query, &nav_mesh, 64, None);
+    assert_eq!(corridor.last_polygon(), *path.polygons.last().unwrap());
+
+    let corners = corridor.find_corners(&nav_mesh, 2).unwrap();
+    assert!(!corners.is_empty() && corners.len() <= 2);
+    assert!(!corners[0].flags.contains(StraightPathFlags::START));
+
+    // Follow the corners until the target is reached.
+    let mut reached = false;
+    for _ in 0..200 {
+        let corners = corridor.find_corners(&nav_mesh, 2).unwrap();
+        let Some(next) = corners.first() else {
+            reached = true;
+            break;
+        };
+
+        corridor.optimize_path_visibility(&nav_mesh, next.position, 10.0, None);
+
+        let position = corridor.position();
+        let step = (next.position - position).clamp_length_max(0.5);
+        corridor
+            .move_position(&nav_mesh, position + step, None)
+            .unwrap();
+
+        assert!(!corridor.polygons().is_empty());
+        assert_eq!(corridor.last_polygon(), *path.polygons.last().unwrap());
+    }
+    assert!(reached, "Corridor never reached the target.");
+    assert!(corridor.position().xz().distance(end_pos.xz()) < 0.1);
+    assert_eq!(corridor.polygons().len(), 1);
+
+    // Moving the target extends the corridor.
+    let new_target = Vec3::new(3.0, 0.1, -5.0);
+    corridor.move_target(&nav_mesh, new_target, None).unwrap();
+    assert!(corridor.target().xz().distance(new_target.xz()) < 0.01);
+    let corners = corridor.find_corners(&nav_mesh, 4).unwrap();
+    let last = corners.last().unwrap();
+    assert!(last.flags.contains(StraightPathFlags::END));
+    assert!(last.position.xz().distance(new_target.xz()) < 0.01);
+}
+
+#[test]
+fn test_poly_ref() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, setup_world_system);
+
+    wait_for_generation_to_finish(&mut app);
+
+    let start_pos = Vec3::new(-15.0, 0.1, -15.0);
+    let end_pos = Vec3::new(15.0, 0.1, 15.0);
+    let (start_polygon, end_polygon, path) = {
+        let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+        let nav_mesh = app.world().resource::<NavMesh>().get();
+        let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+        let (start_polygon, _) = nav_mesh
+            .find_closest_polygon_in_box(nav_mesh_settings, start_pos, 1.0, None)
+            .expect("No polygon found at start position.");
+        let (end_polygon, _) = nav_mesh
+            .find_closest_polygon_in_box(nav_mesh_settings, end_pos, 1.0, None)
+            .expect("No polygon found at end position.");
+        assert_ne!(start_polygon.tile(), end_polygon.tile());
+        assert!(nav_mesh.is_valid_poly_ref(start_polygon));
+        assert!(nav_mesh.is_valid_poly_ref(end_polygon));
+        assert_eq!(
+            nav_mesh.get_poly_ref(start_polygon.tile(), start_polygon.polygon()),
+            Some(start_polygon)
+        );
+
+        let path = find_polygon_path(&nav_mesh, nav_mesh_settings, start_pos, end_pos, None, None)
+            .unwrap();
+        assert!(path
+            .polygons
+            .iter()
+            .all(|polygon| nav_mesh.is_valid_poly_ref(*polygon)));
+
+        (start_polygon, end_polygon, path)
+    };
+
+    // Rebuilds only the tile containing the end position.
+    app.add_systems(Update, setup_island_system.run_if(run_once));
+    // The island is only picked up by the nav-mesh systems on the update after it is spawned.
+    app.update();
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    assert!(nav_mesh.is_valid_poly_ref(start_polygon));
+    assert!(!nav_mesh.is_valid_poly_ref(end_polygon));
+    assert!(nav_mesh.get_tile_and_polygon(end_polygon).is_none());
+
+    // Stale references are rejected instead of pointing to unrelated polygons.
+    assert!(matches!(
+        move_along_surface(&nav_mesh, end_polygon, end_pos, end_pos, None),
+        Err(MoveAlongSurfaceError::InvalidStartPolygon)
+    ));
+    assert!(matches!(
+        perform_string_pulling_on_path(&nav_mesh, start_pos, end_pos, &path.polygons),
+        Err(StringPullingError::InvalidPolygonRef)
+    ));
+
+    // Replacing a tile by hand invalidates references to it as well.
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let mut nav_mesh = nav_mesh.clone();
+    let tile = nav_mesh.tiles[&start_polygon.tile()].clone();
+    nav_mesh.add_tile(start_polygon.tile(), tile, nav_mesh_settings);
+    assert!(!nav_mesh.is_valid_poly_ref(start_polygon));
+    let replaced_polygon = nav_mesh
+        .get_poly_ref(start_polygon.tile(), start_polygon.polygon())
+        .unwrap();
+    assert_ne!(replaced_polygon, start_polygon);
+    assert!(nav_mesh.is_valid_poly_ref(replaced_polygon));
+
+    nav_mesh.remove_tile(start_polygon.tile());
+    assert!(!nav_mesh.is_valid_poly_ref(replaced_polygon));
+}
+
+#[test]
+fn test_flow_field() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, (setup_world_system, setup_island_system));
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    let goal = Vec3::new(10.0, 0.1, -10.0);
+    let flow_field =
+        compute_flow_field(&nav_mesh, nav_mesh_settings, &[goal], None, None, None).unwrap();
+    assert!(flow_field.is_valid(&nav_mesh));
+
+    let (goal_polygon, _) = nav_mesh
+        .find_closest_polygon_in_box(nav_mesh_settings, goal, 1.0, None)
+        .unwrap();
+    let goal_node = flow_field.get_polygon(goal_polygon).unwrap();
+    assert_eq!(goal_node.cost, 0.0);
+    assert!(goal_node.next.is_none());
+
+    // Costs decrease toward the goal.
+    for (polygon, node) in flow_field.polygons() {
+        if let Some(next) = node.next {
+            assert!(
+                flow_field.get_cost(next).unwrap() < node.cost,
+                "{polygon:?} doesn't lead toward the goal."
+            );
+        }
+    }
+
+    // The island is unreachable from the goal.
+    let (island_polygon, _) = nav_mesh
+        .find_closest_polygon_in_box(nav_mesh_settings, Vec3::new(15.0, 5.1, 15.0), 1.0, None)
+        .unwrap();
+    assert!(flow_field.get_polygon(island_polygon).is_none());
+
+    // Following the field from behind the cube reaches the goal.
+    let (mut polygon, mut position) = nav_mesh
+        .find_closest_polygon_in_box(nav_mesh_settings, Vec3::new(-8.0, 0.1, -8.0), 1.0, None)
+        .unwrap();
+    let mut reached = false;
+    for _ in 0..400 {
+        if position.xz().distance(goal.xz()) < 0.25 {
+            reached = true;
+            break;
+        }
+
+        let direction = flow_field
+            .get_direction(&nav_mesh, polygon, position)
+            .expect("Position isn't covered by the flow field.");
+
+        let result = move_along_surface(
+            &nav_mesh,
+            polygon,
+            position,
+            position + direction * 0.25,
+            None,
+        )
+        .unwrap();
+        polygon = *result.polygons.last().unwrap();
+        position = result.position;
+    }
+    assert!(reached, "Flow field never reached the goal.");
+
+    let direction = flow_field
+        .get_direction_at(
+            &nav_mesh,
+            nav_mesh_settings,
+            Vec3::new(0.0, 0.1, -10.0),
+            None,
+        )
+        .unwrap();
+    assert!(direction.x > 0.5, "Unexpected direction: {direction:?}");
+
+    // Cost limited fields only cover polygons close to the goal.
+    let limited =
+        compute_flow_field(&nav_mesh, nav_mesh_settings, &[goal], None, Some(5.0), None).unwrap();
+    assert!(limited.polygons().count() < flow_field.polygons().count());
+    assert!(limited.polygons().all(|(_, node)| node.cost <= 5.0));
+    assert!(limited
+        .get_direction_at(
+            &nav_mesh,
+            nav_mesh_settings,
+            Vec3::new(-20.0, 0.1, 20.0),
+            Some(Vec3::splat(1.0))
+        )
+        .is_none());
+}
+
+#[test]
+fn test_flow_field_area_costs() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, (setup_world_system, setup_area_strip_system));
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    // The goal is on the expensive strip.
+    let (goal_polygon, goal) = nav_mesh
+        .find_closest_polygon_in_box(nav_mesh_settings, Vec3::new(10.0, 0.1, 3.0), 1.0, None)
+        .unwrap();
+    let (goal_tile, _) = nav_mesh.get_tile_and_polygon(goal_polygon).unwrap();
+    assert_eq!(goal_tile.areas[goal_polygon.polygon() as usize], Area(1));
+
+    let filter = DefaultQueryFilter::default().with_area_cost(Area(1), 10.0);
+    let flow_field = compute_flow_field(
+        &nav_mesh,
+        nav_mesh_settings,
+        &[goal],
+        None,
+        None,
+        Some(&filter),
+    )
+    .unwrap();
+
+    // Moving into the goal polygon crosses the strip, whatever area the polygon it's entered from has.
+    let mut entered_from_cheap_area = false;
+    for (polygon, node) in flow_field.polygons() {
+        if node.next != Some(goal_polygon) {
+            continue;
+        }
+
+        let (tile, tile_polygon) = nav_mesh.get_tile_and_polygon(polygon).unwrap();
+        let link = tile_polygon
+            .links
+            .iter()
+            .find(|link| {
+                link.neighbour(polygon.tile()) == (goal_polygon.tile(), goal_polygon.polygon())
+            })
+            .unwrap();
+        let (portal_a, portal_b) = tile.get_link_portal(tile_polygon, link);
+        let expected_cost = portal_a.lerp(portal_b, 0.5).distance(goal) * 10.0;
+        assert!(
+            (node.cost - expected_cost).abs() < 0.01,
+            "Unexpected cost {} for {polygon:?}, expected {expected_cost}",
+            node.cost
+        );
+
+        entered_from_cheap_area |= tile.areas[polygon.polygon() as usize] == Area(0);
+    }
+    assert!(entered_from_cheap_area);
+}
+
+#[test]
+fn test_hierarchical_path() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, (setup_world_system, setup_island_system));
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    // Every tile has an entry in the abstract graph.
+    let abstract_graph = nav_mesh.abstract_graph();
+    assert_eq!(abstract_graph.get_tiles().len(), nav_mesh.tiles.len());
+    let start_tile = nav_mesh_settings.get_tile_containing_position(Vec2::new(-20.0, -20.0));
+    assert!(!abstract_graph.get_tiles()[&start_tile].nodes.is_empty());
+
+    let start_pos = Vec3::new(-20.0, 0.1, -20.0);
+    let end_pos = Vec3::new(20.0, 0.1, 20.0);
+
+    let mut path = find_polygon_path_hierarchical(
+        &nav_mesh,
+        nav_mesh_settings,
+        start_pos,
+        end_pos,
+        None,
+        None,
+    )
+    .unwrap();
+    assert!(path.status.is_complete());
+
+    // Only the part up to the first tile border is refined up front.
+    let full_path =
+        find_polygon_path(&nav_mesh, nav_mesh_settings, start_pos, end_pos, None, None).unwrap();
+    assert!(!path.is_refined());
+    assert!(!path.polygons.is_empty());
+    assert!(path.polygons.len() < full_path.polygons.len());
+    assert!(path
+        .polygons
+        .iter()
+        .all(|polygon| polygon.tile() == start_tile));
+    assert_eq!(
+        path.waypoints().last().unwrap().0,
+        *full_path.polygons.last().unwrap()
+    );
+
+    let mut query = NavMeshQuery::default();
+    let refined_count = path.polygons.len();
+    assert!(!query.refine_hierarchical_path(&nav_mesh, &mut path, 1, None));
+    assert!(path.polygons.len() > refined_count);
+    assert!(query.refine_hierarchical_path(&nav_mesh, &mut path, usize::MAX, None));
+    assert!(path.status.is_complete());
+    assert_eq!(path.polygons.last(), full_path.polygons.last());
+
+    // No polygon is visited twice.
+    for (i, polygon) in path.polygons.iter().enumerate() {
+        assert!(!path.polygons[i + 1..].contains(polygon));
+    }
+
+    let path_length = |points: &[Vec3]| {
+        points
+            .windows(2)
+            .map(|pair| pair[0].distance(pair[1]))
+            .sum::<f32>()
+    };
+    let points =
+        perform_string_pulling_on_path(&nav_mesh, start_pos, end_pos, &path.polygons).unwrap();
+    let optimal_path =
+        find_path(&nav_mesh, nav_mesh_settings, start_pos, end_pos, None, None).unwrap();
+    assert!(path_length(&points) < path_length(&optimal_path.points) * 1.2);
+
+    // The island isn't connected so the abstract graph isn't searched, the regular search finds the closest point instead.
+    let path = find_polygon_path_hierarchical(
+        &nav_mesh,
+        nav_mesh_settings,
+        start_pos,
+        Vec3::new(15.0, 5.1, 15.0),
+        Some(Vec3::splat(1.0)),
+        None,
+    )
+    .unwrap();
+    assert!(path.is_refined());
+    assert!(!path.status.is_complete());
+
+    // Removing a tile removes its nodes & the nodes of its neighbours facing it.
+    let mut nav_mesh = nav_mesh.clone();
+    let total_node_count = |nav_mesh: &NavMeshTiles| {
+        nav_mesh
+            .abstract_graph()
+            .get_tiles()
+            .values()
+            .map(|abstract_tile| abstract_tile.nodes.len())
+            .sum::<usize>()
+    };
+    let node_count = total_node_count(&nav_mesh);
+    let removed_node_count = nav_mesh.abstract_graph().get_tiles()[&start_tile]
+        .nodes
+        .len();
+
+    nav_mesh.remove_tile(start_tile);
+    assert!(!nav_mesh
+        .abstract_graph()
+        .get_tiles()
+        .contains_key(&start_tile));
+    // Every node of the removed tile faced a neighbour, which lost its node facing back.
+    assert_eq!(
+        total_node_count(&nav_mesh),
+        node_count - removed_node_count * 2
+    );
+
+    // Adding the tile back restores the same nodes & moves between them, even though the neighbours only rebuilt their side facing it.
+    let nodes_by_polygons = |nav_mesh: &NavMeshTiles| {
+        let mut nodes = nav_mesh
+            .abstract_graph()
+            .get_tiles()
+            .iter()
+            .flat_map(|(tile_coord, abstract_tile)| {
+                abstract_tile.nodes.iter().map(|node| {
+                    let mut edges = node
+                        .edges
+                        .iter()
+                        .map(|edge| abstract_tile.nodes[edge.node].polygons.clone())
+                        .collect::<Vec<_>>();
+                    edges.sort();
+                    (*tile_coord, node.polygons.clone(), edges)
+                })
+            })
+            .collect::<Vec<_>>();
+        nodes.sort_by_key(|(tile_coord, polygons, _)| (tile_coord.to_array(), polygons.clone()));
+        nodes
+    };
+    let original_nav_mesh = app.world().resource::<NavMesh>().get();
+    let original_nav_mesh = original_nav_mesh.read().unwrap();
+    nav_mesh.add_tile(
+        start_tile,
+        original_nav_mesh.tiles[&start_tile].clone(),
+        nav_mesh_settings,
+    );
+    assert_eq!(
+        nodes_by_polygons(&nav_mesh),
+        nodes_by_polygons(&original_nav_mesh)
+    );
+
+    // Filters only changing costs still find a path through the abstract graph.
+    let filter = DefaultQueryFilter::default().with_area_cost(Area(0), 2.0);
+    let mut path = find_polygon_path_hierarchical(
+        &nav_mesh,
+        nav_mesh_settings,
+        start_pos,
+        end_pos,
+        None,
+        Some(&filter),
+    )
+    .unwrap();
+    assert!(!path.is_refined());
+    assert!(NavMeshQuery::default().refine_hierarchical_path(
+        &nav_mesh,
+        &mut path,
+        usize::MAX,
+        Some(&filter)
+    ));
+    assert!(path.status.is_complete());
+}
+
+#[test]
+fn test_hierarchical_path_filter() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, (setup_world_system, setup_flag_wall_system));
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    let start_pos = Vec3::new(-20.0, 0.1, -20.0);
+    let end_pos = Vec3::new(-20.0, 0.1, 20.0);
+    let filter = DefaultQueryFilter::default().with_excluded_flags(FLAG_DOOR);
+
+    // The abstract path goes around the wall instead of failing to refine through it.
+    let mut path = find_polygon_path_hierarchical(
+        &nav_mesh,
+        nav_mesh_settings,
+        start_pos,
+        end_pos,
+        None,
+        Some(&filter),
+    )
+    .unwrap();
+    assert!(!path.is_refined());
+    for (polygon, position) in path.waypoints() {
+        assert_eq!(nav_mesh.get_polygon_flags(*polygon), Some(0));
+        // The wall's border is only crossed through the gap.
+        if position.z.abs() < 2.5 {
+            assert!(position.x > 20.0);
+        }
+    }
+
+    assert!(NavMeshQuery::default().refine_hierarchical_path(
+        &nav_mesh,
+        &mut path,
+        usize::MAX,
+        Some(&filter)
+    ));
+    assert!(path.status.is_complete());
+    for polygon in &path.polygons {
+        assert_eq!(nav_mesh.get_polygon_flags(*polygon), Some(0));
+    }
+}
+
+#[test]
+fn test_islands() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, (setup_world_system, setup_island_system));
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    let find_polygon = |nav_mesh: &NavMeshTiles, position: Vec3| {
+        nav_mesh
+            .find_closest_polygon_in_box(nav_mesh_settings, position, 1.0, None)
+            .unwrap()
+            .0
+    };
+    let start = find_polygon(&nav_mesh, Vec3::new(-20.0, 0.1, -20.0));
+    let end = find_polygon(&nav_mesh, Vec3::new(20.0, 0.1, 20.0));
+    let island = find_polygon(&nav_mesh, Vec3::new(15.0, 5.1, 15.0));
+
+    assert!(nav_mesh.island_of(start).is_some());
+    assert!(nav_mesh.are_connected(start, end));
+    assert!(!nav_mesh.are_connected(start, island));
+    assert!(!nav_mesh.are_connected(end, island));
+
+    // Removing a tile keeps the rest of the plane connected & invalidates references into it.
+    let mut nav_mesh = nav_mesh.clone();
+    let removed_tile = nav_mesh_settings.get_tile_containing_position(Vec2::new(-20.0, 20.0));
+    let removed = find_polygon(&nav_mesh, Vec3::new(-20.0, 0.1, 20.0));
+    assert_eq!(removed.tile(), removed_tile);
+    let removed_tile_data = nav_mesh.tiles[&removed_tile].clone();
+
+    nav_mesh.remove_tile(removed_tile);
+    assert!(nav_mesh.island_of(removed).is_none());
+    assert!(!nav_mesh.are_connected(start, removed));
+    assert!(nav_mesh.are_connected(start, end));
+    assert!(!nav_mesh.are_connected(start, island));
+
+    // Removing the other tile between the start & end splits the plane.
+    nav_mesh.remove_tile(nav_mesh_settings.get_tile_containing_position(Vec2::new(20.0, -20.0)));
+    assert!(!nav_mesh.are_connected(start, end));
+
+    // Adding a tile back merges the halves again.
+    nav_mesh.add_tile(removed_tile, removed_tile_data, nav_mesh_settings);
+    assert!(nav_mesh.are_connected(start, end));
+    assert!(!nav_mesh.are_connected(start, island));
+
+    // Islands not touching a changed tile or its neighbours keep their ids.
+    let island_id = nav_mesh.island_of(island);
+    nav_mesh.remove_tile(start.tile());
+    assert!(island_id.is_some());
+    assert_eq!(nav_mesh.island_of(island), island_id);
+    assert!(nav_mesh.island_of(end).is_some());
+}
+
+#[derive(Resource, Default)]
+struct FinishedTickets(Vec<PathTicket>);
+
+fn collect_finished_tickets_system(
+    mut events: EventReader<PathfindingFinished>,
+    mut finished_tickets: ResMut<FinishedTickets>,
+) {
+    for event in events.read() {
+        assert!(
+            event.result.is_ok(),
+            "Pathfinding failed: {:?}",
+            event.result
+        );
+        finished_tickets.0.push(event.ticket);
+    }
+}
+
+#[test]
+fn test_pathfinding_tickets() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.init_resource::<FinishedTickets>()
+        .add_systems(Startup, setup_world_system)
+        .add_systems(Update, collect_finished_tickets_system);
+
+    wait_for_generation_to_finish(&mut app);
+
+    let mut pathfinding_queue = app.world_mut().resource_mut::<PathfindingQueue>();
+    pathfinding_queue.set_max_tasks(NonZeroU16::new(1));
+
+    let start_pos = Vec3::new(5.0, 1.0, 5.0);
+    let end_pos = Vec3::new(-15.0, 1.0, -15.0);
+    let low = pathfinding_queue.submit(PathfindingRequest::new(start_pos, end_pos));
+    let high =
+        pathfinding_queue.submit(PathfindingRequest::new(start_pos, end_pos).with_priority(10));
+    let medium =
+        pathfinding_queue.submit(PathfindingRequest::new(start_pos, end_pos).with_priority(5));
+    let cancelled =
+        pathfinding_queue.submit(PathfindingRequest::new(start_pos, end_pos).with_priority(5));
+
+    assert_eq!(
+        pathfinding_queue.get_status(low),
+        Some(PathTicketStatus::Queued)
+    );
+    assert!(pathfinding_queue.cancel(cancelled));
+    assert!(pathfinding_queue.get_status(cancelled).is_none());
+
+    app.update();
+
+    // Only the highest priority request may run.
+    let pathfinding_queue = app.world().resource::<PathfindingQueue>();
+    assert!(pathfinding_queue.running_len() <= 1);
+    assert_ne!(
+        pathfinding_queue.get_status(low),
+        Some(PathTicketStatus::Running)
+    );
+
+    let start_time = std::time::Instant::now();
+    while app.world().resource::<FinishedTickets>().0.len() < 3 {
+        assert!(
+            start_time.elapsed() < TIMEOUT_DURATION,
+            "Pathfinding timed out."
+        );
+
+        app.update();
+        std::thread::sleep(SLEEP_DURATION);
+    }
+
+    // Requests finish in priority order as only one may run at a time.
+    assert_eq!(
+        app.world().resource::<FinishedTickets>().0,
+        vec![high, medium, low]
+    );
+
+    // Results are kept until they're taken, even after later updates.
+    for _ in 0..3 {
+        app.update();
+    }
+    let mut pathfinding_queue = app.world_mut().resource_mut::<PathfindingQueue>();
+    for ticket in [high, medium, low] {
+        assert_eq!(
+            pathfinding_queue.get_status(ticket),
+            Some(PathTicketStatus::Finished)
+        );
+        assert!(pathfinding_queue.take_result(ticket).unwrap().is_ok());
+        assert!(pathfinding_queue.get_status(ticket).is_none());
+    }
+
+    let ticket = pathfinding_queue.submit(PathfindingRequest::new(start_pos, end_pos));
+    let start_time = std::time::Instant::now();
+    let result = loop {
+        assert!(
+            start_time.elapsed() < TIMEOUT_DURATION,
+            "Pathfinding timed out."
+        );
+
+        app.update();
+        std::thread::sleep(SLEEP_DURATION);
+
+        if let Some(result) = app
+            .world_mut()
+            .resource_mut::<PathfindingQueue>()
+            .take_result(ticket)
+        {
+            break result;
+        }
+    };
+    assert!(result.unwrap().status.is_complete());
+}
+
+#[derive(Resource, Default)]
+struct FailedPathRequests(Vec<Entity>);
+
+fn collect_failed_path_requests_system(
+    mut events: EventReader<PathRequestFailed>,
+    mut failed_path_requests: ResMut<FailedPathRequests>,
+) {
+    for event in events.read() {
+        failed_path_requests.0.push(event.entity);
+    }
+}
+
+#[test]
+fn test_path_request() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.init_resource::<FailedPathRequests>()
+        .add_systems(Startup, setup_world_system)
+        .add_systems(Update, collect_failed_path_requests_system);
+
+    wait_for_generation_to_finish(&mut app);
+
+    let target = Vec3::new(-15.0, 1.0, -15.0);
+    let agent = app
+        .world_mut()
+        .spawn((
+            Transform::from_xyz(5.0, 1.0, 5.0),
+            NavAgent::default().with_area_cost(Area(1), 2.0),
+            PathRequest { target },
+        ))
+        .id();
+    let unreachable = app
+        .world_mut()
+        .spawn((
+            Transform::from_xyz(5.0, 1.0, 5.0),
+            PathRequest {
+                target: Vec3::new(100.0, 1.0, 100.0),
+            },
+        ))
+        .id();
+
+    let start_time = std::time::Instant::now();
+    while app.world().get::<NavPath>(agent).is_none()
+        || app.world().resource::<FailedPathRequests>().0.is_empty()
+    {
+        assert!(
+            start_time.elapsed() < TIMEOUT_DURATION,
+            "Path request timed out."
+        );
+
+        app.update();
+        std::thread::sleep(SLEEP_DURATION);
+    }
+
+    let nav_path = app.world().get::<NavPath>(agent).unwrap();
+    assert!(nav_path.status.is_complete());
+    // The path starts at the spawn position even though transforms hadn't been propagated yet.
+    assert!(nav_path.corners[0].xz().distance(Vec2::new(5.0, 5.0)) < 0.5);
+    assert!(nav_path.corners.last().unwrap().xz().distance(target.xz()) < 0.5);
+    assert!(app.world().get::<PathRequest>(agent).is_none());
+
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let mut nav_mesh = nav_mesh.read().unwrap().clone();
+    assert!(!nav_path.is_outdated(&nav_mesh));
+
+    // Only changes to the tiles the path crosses outdate it.
+    let other_tile = nav_mesh.tiles.keys().copied().find(|tile| {
+        nav_path
+            .polygons
+            .iter()
+            .all(|polygon| polygon.tile() != *tile)
+    });
+    if let Some(other_tile) = other_tile {
+        nav_mesh.remove_tile(other_tile);
+        assert!(!nav_path.is_outdated(&nav_mesh));
+    }
+    nav_mesh.remove_tile(nav_path.polygons[0].tile());
+    assert!(nav_path.is_outdated(&nav_mesh));
+
+    assert_eq!(
+        app.world().resource::<FailedPathRequests>().0,
+        vec![unreachable]
+    );
+    assert!(app.world().get::<PathRequest>(unreachable).is_none());
+    assert!(app.world().get::<NavPath>(unreachable).is_none());
+}
+
+#[derive(Resource, Default)]
+struct PathFollowingEvents {
+    completed: Vec<Entity>,
+    failed: Vec<Entity>,
+}
+
+fn collect_path_following_events_system(
+    mut completed_events: EventReader<PathCompleted>,
+    mut failed_events: EventReader<PathFailed>,
+    mut path_following_events: ResMut<PathFollowingEvents>,
+) {
+    for event in completed_events.read() {
+        path_following_events.completed.push(event.entity);
+    }
+    for event in failed_events.read() {
+        path_following_events.failed.push(event.entity);
+    }
+}
+
+#[test]
+fn test_path_following() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_plugins(PathFollowingPlugin)
+        .init_resource::<PathFollowingEvents>()
+        .add_systems(Startup, setup_world_system)
+        .add_systems(Update, collect_path_following_events_system);
+
+    wait_for_generation_to_finish(&mut app);
+
+    // Step time manually so movement doesn't depend on how fast the test runs.
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        50,
+    )));
+
+    let target = Vec3::new(-15.0, 0.1, -15.0);
+    // A small arrival radius so corners are walked up to instead of being skipped early.
+    let follower = PathFollower::default()
+        .with_max_speed(8.0)
+        .with_arrival_radius(0.01)
+        .with_stopping_distance(0.2);
+    let agent = app
+        .world_mut()
+        .spawn((
+            Transform::from_xyz(5.0, 0.1, 5.0),
+            follower.clone(),
+            PathRequest { target },
+        ))
+        .id();
+    let unreachable = app
+        .world_mut()
+        .spawn((
+            Transform::from_xyz(5.0, 0.1, 5.0),
+            follower,
+            PathRequest {
+                target: Vec3::new(100.0, 1.0, 100.0),
+            },
+        ))
+        .id();
+
+    let start_time = std::time::Instant::now();
+    loop {
+        let events = app.world().resource::<PathFollowingEvents>();
+        if !events.completed.is_empty() && !events.failed.is_empty() {
+            break;
+        }
+        assert!(
+            start_time.elapsed() < TIMEOUT_DURATION,
+            "Path following timed out."
+        );
+
+        let previous_position = app.world().get::<Transform>(agent).unwrap().translation;
+        let previous_speed = app
+            .world()
+            .get::<PathFollower>(agent)
+            .map_or(0.0, |follower| follower.velocity().length());
+
+        app.update();
+
+        // The agent stays on the plane & never cuts through the cube.
+        let position = app.world().get::<Transform>(agent).unwrap().translation;
+
+        // At full speed the whole step is moved, even when it passes a corner.
+        let speed = app
+            .world()
+            .get::<PathFollower>(agent)
+            .map_or(0.0, |follower| follower.velocity().length());
+        if previous_speed > 7.99 && speed > 7.99 {
+            let step = position.xz().distance(previous_position.xz());
+            assert!(step > 8.0 * 0.05 * 0.9, "Lost part of a step: {step}");
+        }
+
+        assert!((position.y - 0.1).abs() < 0.2, "Not snapped: {position}");
+        assert!(
+            !(position.x > -6.5 && position.x < -3.5 && position.z > -6.5 && position.z < -3.5),
+            "Walked through the cube: {position}"
+        );
+    }
+
+    let events = app.world().resource::<PathFollowingEvents>();
+    assert_eq!(events.completed, vec![agent]);
+    assert_eq!(events.failed, vec![unreachable]);
+
+    let position = app.world().get::<Transform>(agent).unwrap().translation;
+    assert!(position.xz().distance(target.xz()) <= 0.25);
+    assert!(app.world().get::<NavPath>(agent).is_none());
+    assert_eq!(
+        app.world().get::<PathFollower>(agent).unwrap().velocity(),
+        Vec3::ZERO
+    );
+}
+
+#[test]
+fn test_crowd() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, setup_world_system);
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    // Four agents swapping corners of a square, all crossing in the middle.
+    let routes = [
+        (Vec3::new(1.0, 0.1, 1.0), Vec3::new(7.0, 0.1, 7.0)),
+        (Vec3::new(7.0, 0.1, 7.0), Vec3::new(1.0, 0.1, 1.0)),
+        (Vec3::new(1.0, 0.1, 7.0), Vec3::new(7.0, 0.1, 1.0)),
+        (Vec3::new(7.0, 0.1, 1.0), Vec3::new(1.0, 0.1, 7.0)),
+    ];
+    // One agent walking straight into the cube's side.
+    let wall_route = (Vec3::new(-5.0, 0.1, 0.0), Vec3::new(-5.0, 0.1, -8.0));
+
+    let params = CrowdAgentParams::default().with_radius(0.5);
+    let mut crowd = Crowd::default();
+    let mut agents = Vec::new();
+    for (start, end) in routes {
+        let agent = crowd
+            .add_agent(&nav_mesh, nav_mesh_settings, start, params.clone(), None)
+            .expect("No polygon found at start position.");
+        let path = find_path(&nav_mesh, nav_mesh_settings, start, end, None, None).unwrap();
+        assert!(crowd.set_path(agent, &path));
+
+        agents.push((agent, end));
+    }
+    let wall_agent = crowd
+        .add_agent(
+            &nav_mesh,
+            nav_mesh_settings,
+            wall_route.0,
+            params.clone(),
+            None,
+        )
+        .unwrap();
+    // A path straight through the cube, as if the nav-mesh changed after it was found.
+    assert!(crowd.set_path(
+        wall_agent,
+        &Path {
+            points: vec![wall_route.0, wall_route.1],
+            status: PathStatus::Complete,
+        }
+    ));
+
+    for i in 0..300 {
+        if i == 150 {
+            // Walls are searched for again on the next update, nothing else changes.
+            crowd.invalidate_walls();
+        }
+        crowd.update(&nav_mesh, nav_mesh_settings, 1.0 / 30.0, None);
+
+        let positions = agents
+            .iter()
+            .map(|(agent, _)| crowd.get_agent(*agent).unwrap().position())
+            .collect::<Vec<_>>();
+        for (i, a) in positions.iter().enumerate() {
+            assert!((a.y - 0.1).abs() < 0.2, "Left the nav-mesh: {a}");
+
+            for b in positions[i + 1..].iter() {
+                // Allow a little overlap, avoidance is only guaranteed within the time horizon.
+                assert!(
+                    a.xz().distance(b.xz()) > params.radius * 2.0 - 0.2,
+                    "Agents overlap: {a} {b}"
+                );
+            }
+        }
+
+        let position = crowd.get_agent(wall_agent).unwrap().position();
+        assert!(
+            !(position.x > -6.5 && position.x < -3.5 && position.z > -6.5 && position.z < -3.5),
+            "Walked through the cube: {position}"
+        );
+    }
+
+    // The wall agent stopped in front of the cube, keeping its radius from the wall.
+    let wall_agent_state = crowd.get_agent(wall_agent).unwrap();
+    assert!(wall_agent_state.velocity().length() < 0.1);
+    assert!(nav_mesh
+        .get_height_at(
+            nav_mesh_settings,
+            wall_agent_state.position() - Vec3::Z * params.radius * 0.9,
+            1.0
+        )
+        .is_some());
+
+    for (agent, end) in agents {
+        let agent = crowd.get_agent(agent).unwrap();
+        assert!(agent.has_arrived(), "Didn't arrive: {}", agent.position());
+        assert!(agent.position().xz().distance(end.xz()) < 0.5);
+    }
+
+    // Removed agents are gone for good, even when their slot is reused.
+    let removed = crowd.remove_agent(wall_agent);
+    assert!(removed.is_some());
+    assert!(crowd.get_agent(wall_agent).is_none());
+    let reused = crowd
+        .add_agent(&nav_mesh, nav_mesh_settings, wall_route.0, params, None)
+        .unwrap();
+    assert_ne!(reused, wall_agent);
+    assert!(crowd.get_agent(wall_agent).is_none());
+    assert_eq!(crowd.agents().count(), 5);
+}
+
+fn setup_ridge_world_system(mut commands: Commands) {
+    // Plane
+    commands.spawn((
+        Transform::IDENTITY,
+        MyParryCollider {
+            collider: SharedShape::cuboid(25.0, 0.1, 25.0),
+        },
+        NavMeshAffector,
+    ));
+
+    // Ridge with 30 degree sides between x 9 & 15, ending at z 6 & 18.
+    let height = 3.0 * 30.0_f32.to_radians().tan();
+    let points = [6.0, 18.0].into_iter().flat_map(|z| {
+        [
+            Point::new(9.0, 0.0, z),
+            Point::new(15.0, 0.0, z),
+            Point::new(12.0, height, z),
+        ]
+    });
+    commands.spawn((
+        Transform::from_xyz(0.0, 0.1, 0.0),
+        MyParryCollider {
+            collider: SharedShape::convex_hull(&points.collect::<Vec<_>>()).unwrap(),
+        },
+        NavMeshAffector,
+    ));
+}
+
+#[test]
+fn test_slope_costs() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, setup_ridge_world_system);
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    let start_pos = Vec3::new(5.0, 0.1, 12.0);
+    let end_pos = Vec3::new(19.0, 0.1, 12.0);
+    // Paths crossing the ridge stay between its ends, detours go around them.
+    let crosses_ridge = |filter: &DefaultQueryFilter| {
+        let path = find_path(
+            &nav_mesh,
+            nav_mesh_settings,
+            start_pos,
+            end_pos,
+            None,
+            Some(filter),
+        )
+        .expect("Failed to find path.");
+        assert!(path.status.is_complete());
+
+        path.points
+            .iter()
+            .all(|point| point.z > 6.0 && point.z < 18.0)
+    };
+
+    // Without slope costs the shortest path climbs over the ridge.
+    assert!(crosses_ridge(&DefaultQueryFilter::default()));
+
+    // Cheap slopes are still worth climbing.
+    let cheap = SlopeCosts::default()
+        .with_uphill_cost(0.1)
+        .with_downhill_cost(0.1);
+    assert!(crosses_ridge(
+        &DefaultQueryFilter::default().with_slope_costs(cheap)
+    ));
+
+    // Expensive climbing makes the detour cheaper.
+    let expensive_uphill = SlopeCosts::default().with_uphill_cost(50.0);
+    assert!(!crosses_ridge(
+        &DefaultQueryFilter::default().with_slope_costs(expensive_uphill)
+    ));
+
+    // Slopes steeper than the limit are never taken, no matter the detour.
+    let limited = SlopeCosts::default().with_max_uphill_slope(20.0_f32.to_radians());
+    assert!(!crosses_ridge(
+        &DefaultQueryFilter::default().with_slope_costs(limited)
+    ));
+    let limited = SlopeCosts::default().with_max_downhill_slope(20.0_f32.to_radians());
+    assert!(!crosses_ridge(
+        &DefaultQueryFilter::default().with_slope_costs(limited)
+    ));
+    let limited = SlopeCosts::default().with_max_uphill_slope(35.0_f32.to_radians());
+    assert!(crosses_ridge(
+        &DefaultQueryFilter::default().with_slope_costs(limited)
+    ));
+
+    // Uphill & downhill are priced separately.
+    let slope_costs = SlopeCosts::default()
+        .with_uphill_cost(2.0)
+        .with_downhill_cost(1.0);
+    let from = Vec3::ZERO;
+    let to = Vec3::new(1.0, 1.0, 0.0);
+    let slope = std::f32::consts::FRAC_PI_4;
+    assert!((slope_costs.get_multiplier(from, to) - (1.0 + slope * 2.0)).abs() < 0.001);
+    assert!((slope_costs.get_multiplier(to, from) - (1.0 + slope)).abs() < 0.001);
+    assert_eq!(slope_costs.get_multiplier(from, Vec3::X), 1.0);
+}
+
+#[test]
+fn test_project_point() {
+    let mut app = App::new();
+
+    setup_app(&mut app);
+
+    app.add_systems(Startup, (setup_world_system, setup_island_system));
+
+    wait_for_generation_to_finish(&mut app);
+
+    let nav_mesh_settings = app.world().resource::<NavMeshSettings>();
+    let nav_mesh = app.world().resource::<NavMesh>().get();
+    let nav_mesh = nav_mesh.read().expect("Failed to get nav-mesh lock.");
+
+    // The island is a second floor above the ground. Each floor is kept with a small vertical extent.
+    let extents = Vec3::new(2.0, 1.0, 2.0);
+    let upper = nav_mesh
+        .project_point(nav_mesh_settings, Vec3::new(15.0, 5.5, 15.0), extents, None)
+        .expect("No polygon found on the island.");
+    assert!(upper.is_over_polygon);
+    assert!((upper.position.y - 5.1).abs() < 0.2);
+    assert_eq!(upper.position.xz(), Vec2::new(15.0, 15.0));
+
+    let lower = nav_mesh
+        .project_point(nav_mesh_settings, Vec3::new(15.0, 0.5, 15.0), extents, None)
+        .expect("No polygon found below the island.");
+    assert!(lower.is_over_polygon);
+    assert!((lower.position.y - 0.1).abs() < 0.2);
+    assert!(!nav_mesh.are_connected(upper.polygon, lower.polygon));
+
+    // Between the floors nothing is in vertical range.
+    assert!(nav_mesh
+        .project_point(nav_mesh_settings, Vec3::new(15.0, 2.6, 15.0), extents, None)
+        .is_none());
+
+    // Inside the cube the closest point is on its edge.
+    let projected = nav_mesh
+        .project_point(
+            nav_mesh_settings,
+            Vec3::new(-5.0, 0.1, -4.5),
+            Vec3::new(3.0, 1.0, 3.0),
+            None,
+        )
+        .expect("No polygon found near the cube.");
+    assert!(!projected.is_over_polygon);
+    assert!(
+        projected.position.z > -3.75 && projected.position.z < -3.0,
+        "Unexpected position: {}",
+        projected.position
+    );
+
+    // Pathfinding stays on the floor it starts on.
+    let path = find_path(
+        &nav_mesh,
+        nav_mesh_settings,
+        Vec3::new(14.0, 5.5, 14.0),
+        Vec3::new(16.0, 5.5, 16.0),
+        Some(extents),
+        None,
+    )
+    .unwrap();
+    assert!(path.status.is_complete());
+    assert!(path.points.iter().all(|point| point.y > 4.5));
+
+    assert!(matches!(
+        find_path(
+            &nav_mesh,
+            nav_mesh_settings,
+            Vec3::new(14.0, 2.6, 14.0),
+            Vec3::new(16.0, 5.5, 16.0),
+            Some(extents),
+            None,
+        ),
+        Err(FindPathError::PolygonPath(
+            FindPolygonPathError::NoValidStartPolygon
+        ))
+    ));
 }
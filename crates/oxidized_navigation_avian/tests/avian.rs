@@ -16,7 +16,7 @@ use bevy::{
     prelude::*,
 };
 use oxidized_navigation::{
-    query::{find_path, FindPathError},
+    query::{find_path, FindPathError, Path},
     tiles::{NavMeshTile, NavMeshTiles},
     ActiveGenerationTasks, NavMesh, NavMeshAffector, NavMeshSettings, OxidizedNavigationPlugin,
 };
@@ -160,7 +160,7 @@ trait TestApp {
     fn setup_compound_world(&mut self) -> &mut Self;
     fn setup_compound_world_without_thin_wall(&mut self) -> &mut Self;
     fn clear_world(&mut self) -> &mut Self;
-    fn run_pathfinding(&self) -> Result<Vec<Vec3>, FindPathError>;
+    fn run_pathfinding(&self) -> Result<Path, FindPathError>;
     fn get_nav_mesh(&self) -> NavMeshTiles;
 }
 
@@ -411,7 +411,7 @@ impl TestApp for App {
         self
     }
 
-    fn run_pathfinding(&self) -> Result<Vec<Vec3>, FindPathError> {
+    fn run_pathfinding(&self) -> Result<Path, FindPathError> {
         let nav_mesh_settings = self.world().resource::<NavMeshSettings>();
         let nav_mesh = self.get_nav_mesh();
 
@@ -16,9 +16,9 @@ use bevy::tasks::futures_lite::future;
 use bevy_rapier3d::prelude::{Collider, NoUserData, RapierPhysicsPlugin};
 use oxidized_navigation::{
     debug_draw::{DrawNavMesh, DrawPath, OxidizedNavigationDebugDrawPlugin},
-    query::{find_path, find_polygon_path, perform_string_pulling_on_path},
+    query::{find_path, find_polygon_path, perform_string_pulling_on_path, DefaultQueryFilter},
     tiles::NavMeshTiles,
-    NavMesh, NavMeshAffector, NavMeshSettings, OxidizedNavigationPlugin,
+    Area, NavMesh, NavMeshAffector, NavMeshSettings, OxidizedNavigationPlugin,
 };
 use oxidized_navigation_rapier::RapierCollider;
 
@@ -84,13 +84,14 @@ fn run_blocking_pathfinding(
             start_pos,
             end_pos,
             None,
-            Some(&[1.0, 0.5]),
+            Some(&DefaultQueryFilter::default().with_area_cost(Area(1), 0.5)),
         ) {
             Ok(path) => {
                 info!("Path found (BLOCKING): {:?}", path);
 
                 // Convert polygon path to a path of Vec3s.
-                match perform_string_pulling_on_path(&nav_mesh, start_pos, end_pos, &path) {
+                match perform_string_pulling_on_path(&nav_mesh, start_pos, end_pos, &path.polygons)
+                {
                     Ok(string_path) => {
                         info!("String path (BLOCKING): {:?}", string_path);
                         commands.spawn(DrawPath {
@@ -178,7 +179,7 @@ async fn async_path_find(
     nav_mesh_settings: NavMeshSettings,
     start_pos: Vec3,
     end_pos: Vec3,
-    position_search_radius: Option<f32>,
+    position_search_extents: Option<Vec3>,
 ) -> Option<Vec<Vec3>> {
     // Get the underlying nav_mesh.
     let Ok(nav_mesh) = nav_mesh_lock.read() else {
@@ -191,12 +192,12 @@ async fn async_path_find(
         &nav_mesh_settings,
         start_pos,
         end_pos,
-        position_search_radius,
-        Some(&[1.0, 0.5]),
+        position_search_extents,
+        Some(&DefaultQueryFilter::default().with_area_cost(Area(1), 0.5)),
     ) {
         Ok(path) => {
             info!("Found path (ASYNC): {:?}", path);
-            return Some(path);
+            return Some(path.points);
         }
         Err(error) => error!("Error with pathfinding: {:?}", error),
     }